@@ -0,0 +1,415 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt::{Debug, Display},
+    mem,
+    sync::RwLock,
+};
+
+use crate::skiplist::{HeightGenerator, Mt19937Heights};
+
+/// Marks the end of a level.
+const NIL: u32 = u32::MAX;
+/// Stands for the header wherever a node index is expected.
+const HEAD: u32 = u32::MAX - 1;
+
+/// A concurrent ordered set backed by an arena-allocated skiplist.
+///
+/// Nodes live in one contiguous slab and their links in a second one, as
+/// `u32` indices rather than `Arc`s. Erased slots are recycled by later
+/// inserts. Locking works as in [`SkipList`](crate::SkipList): one lock
+/// over the whole list.
+///
+/// With `i32` keys on a 64-bit target, a node takes 12 bytes plus 4 per
+/// level, about 17 bytes per key on average. With the slabs' spare
+/// capacity, 100 000 keys come to 26 bytes per key. The same keys in a
+/// [`SkipList`](crate::SkipList), where every node is an `Arc<RwLock<_>>`
+/// plus separately allocated vectors of links and link widths, take 125
+/// bytes per key before allocator overhead. See [`allocated_bytes`](Self::allocated_bytes).
+///
+/// ```
+/// use p0::ArenaSkipList;
+///
+/// let list = ArenaSkipList::<i32>::new();
+/// assert!(list.insert(2));
+/// assert!(list.insert(1));
+/// assert!(!list.insert(2));
+///
+/// assert!(list.contains(&1));
+/// assert!(list.erase(&1));
+/// assert!(!list.contains(&1));
+/// assert_eq!(list.size(), 1);
+/// ```
+pub struct ArenaSkipList<K: Ord, const MAX_HEIGHT: usize = 14, const SEED: u32 = 15445> {
+    inner: RwLock<ArenaInner<K, MAX_HEIGHT, SEED>>,
+}
+
+impl<K: Ord, const MAX_HEIGHT: usize, const SEED: u32> ArenaSkipList<K, MAX_HEIGHT, SEED> {
+    /// Creates an empty list.
+    pub fn new() -> Self {
+        ArenaSkipList {
+            inner: RwLock::new(ArenaInner::new()),
+        }
+    }
+
+    /// Returns `true` if the list holds no keys.
+    pub fn empty(&self) -> bool {
+        self.inner.read().unwrap().size == 0
+    }
+
+    /// Returns the number of keys in the list.
+    pub fn size(&self) -> usize {
+        self.inner.read().unwrap().size
+    }
+
+    /// Inserts `key`, returning `false` if it was already present.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the arena already holds `u32::MAX - 1` nodes, or if its
+    /// slab of links has grown past `u32::MAX` entries.
+    pub fn insert(&self, key: K) -> bool {
+        let mut inner = self.inner.write().unwrap();
+        inner.insert(key)
+    }
+
+    /// Removes `key`, returning `false` if it was not present.
+    pub fn erase<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut inner = self.inner.write().unwrap();
+        inner.erase(key)
+    }
+
+    /// Returns `true` if `key` is in the list.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.inner.read().unwrap().find(key).is_some()
+    }
+
+    /// Removes every key from the list and releases the arena.
+    pub fn clear(&self) {
+        let mut inner = self.inner.write().unwrap();
+        inner.clear();
+    }
+
+    /// Returns the number of bytes the list has allocated, counting the
+    /// spare capacity of both slabs.
+    pub fn allocated_bytes(&self) -> usize {
+        let inner = self.inner.read().unwrap();
+        mem::size_of::<Self>()
+            + inner.slots.capacity() * mem::size_of::<Slot<K>>()
+            + inner.links.capacity() * mem::size_of::<u32>()
+            + inner
+                .free_links
+                .iter()
+                .map(|free| free.capacity() * mem::size_of::<u32>())
+                .sum::<usize>()
+    }
+}
+
+impl<K: Ord, const MAX_HEIGHT: usize, const SEED: u32> Default
+    for ArenaSkipList<K, MAX_HEIGHT, SEED>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Debug, const MAX_HEIGHT: usize, const SEED: u32> Display
+    for ArenaSkipList<K, MAX_HEIGHT, SEED>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.inner.read().unwrap(), f)
+    }
+}
+
+impl<K: Ord + Debug, const MAX_HEIGHT: usize, const SEED: u32> Debug
+    for ArenaSkipList<K, MAX_HEIGHT, SEED>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.inner.read().unwrap(), f)
+    }
+}
+
+enum Slot<K> {
+    Occupied {
+        key: K,
+        height: u8,
+        /// Offset of the node's links in `ArenaInner::links`.
+        links: u32,
+    },
+    Vacant {
+        next_free: u32,
+    },
+}
+
+struct ArenaInner<K: Ord, const MAX_HEIGHT: usize, const SEED: u32> {
+    head: [u32; MAX_HEIGHT],
+    slots: Vec<Slot<K>>,
+    links: Vec<u32>,
+    /// Head of the list of vacant slots, threaded through `Slot::Vacant`.
+    free_slot: u32,
+    /// Offsets of released link ranges, by tower height minus one.
+    free_links: Vec<Vec<u32>>,
+    height: usize,
+    size: usize,
+    heights: Mt19937Heights,
+}
+
+impl<K: Ord, const MAX_HEIGHT: usize, const SEED: u32> ArenaInner<K, MAX_HEIGHT, SEED> {
+    fn new() -> Self {
+        assert!(
+            MAX_HEIGHT <= u8::MAX as usize,
+            "ArenaSkipList towers cannot be taller than {}",
+            u8::MAX
+        );
+        ArenaInner {
+            head: [NIL; MAX_HEIGHT],
+            slots: Vec::new(),
+            links: Vec::new(),
+            free_slot: NIL,
+            free_links: (0..MAX_HEIGHT).map(|_| Vec::new()).collect(),
+            height: 1,
+            size: 0,
+            heights: Mt19937Heights::seeded(SEED),
+        }
+    }
+
+    fn insert(&mut self, key: K) -> bool {
+        let (update, found) = self.trace(&key);
+        if found {
+            return false;
+        }
+
+        let new_height = self.heights.next_height(MAX_HEIGHT);
+        if new_height > self.height {
+            self.height = new_height;
+        }
+        let node = self.allocate(key, new_height);
+        for (level, &pred) in update.iter().enumerate().take(new_height) {
+            let next = self.next(pred, level);
+            self.set_next(node, level, next);
+            self.set_next(pred, level, node);
+        }
+        self.size += 1;
+
+        true
+    }
+
+    fn erase<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (update, found) = self.trace(key);
+        if !found {
+            return false;
+        }
+
+        let node = self.next(update[0], 0);
+        for (level, &pred) in update.iter().enumerate().take(self.node_height(node)) {
+            if self.next(pred, level) == node {
+                let next = self.next(node, level);
+                self.set_next(pred, level, next);
+            }
+        }
+        self.release(node);
+        self.size -= 1;
+
+        while self.height > 1 && self.head[self.height - 1] == NIL {
+            self.height -= 1;
+        }
+        true
+    }
+
+    fn find<Q>(&self, key: &Q) -> Option<u32>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut cur = HEAD;
+        for level in (0..self.height).rev() {
+            loop {
+                let next = self.next(cur, level);
+                if next == NIL {
+                    break;
+                }
+                match self.key(next).borrow().cmp(key) {
+                    Ordering::Less => cur = next,
+                    Ordering::Equal => return Some(next),
+                    Ordering::Greater => break,
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the predecessor of `key` on every level, bottom level first,
+    /// and whether `key` is present.
+    fn trace<Q>(&self, key: &Q) -> ([u32; MAX_HEIGHT], bool)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut update = [HEAD; MAX_HEIGHT];
+        let mut cur = HEAD;
+        let mut found = false;
+        for level in (0..MAX_HEIGHT).rev() {
+            loop {
+                let next = self.next(cur, level);
+                if next == NIL {
+                    break;
+                }
+                match self.key(next).borrow().cmp(key) {
+                    Ordering::Less => cur = next,
+                    Ordering::Equal => {
+                        found = true;
+                        break;
+                    }
+                    Ordering::Greater => break,
+                }
+            }
+            update[level] = cur;
+        }
+        (update, found)
+    }
+
+    fn clear(&mut self) {
+        self.head = [NIL; MAX_HEIGHT];
+        self.slots = Vec::new();
+        self.links = Vec::new();
+        self.free_slot = NIL;
+        self.free_links
+            .iter_mut()
+            .for_each(|free| *free = Vec::new());
+        self.height = 1;
+        self.size = 0;
+    }
+
+    fn allocate(&mut self, key: K, height: usize) -> u32 {
+        let links = match self.free_links[height - 1].pop() {
+            Some(offset) => offset,
+            None => {
+                let offset = self.links.len();
+                let links = u32::try_from(offset)
+                    .expect("ArenaSkipList link slab is past u32::MAX entries");
+                self.links.resize(offset + height, NIL);
+                links
+            }
+        };
+        let slot = Slot::Occupied {
+            key,
+            height: height as u8,
+            links,
+        };
+
+        if self.free_slot != NIL {
+            let node = self.free_slot;
+            match mem::replace(&mut self.slots[node as usize], slot) {
+                Slot::Vacant { next_free } => self.free_slot = next_free,
+                Slot::Occupied { .. } => unreachable!("Free list should only hold vacant slots"),
+            }
+            return node;
+        }
+
+        let node = u32::try_from(self.slots.len())
+            .ok()
+            .filter(|&node| node < HEAD)
+            .expect("ArenaSkipList is full");
+        self.slots.push(slot);
+        node
+    }
+
+    fn release(&mut self, node: u32) {
+        let vacant = Slot::Vacant {
+            next_free: self.free_slot,
+        };
+        match mem::replace(&mut self.slots[node as usize], vacant) {
+            Slot::Occupied { height, links, .. } => {
+                self.free_links[height as usize - 1].push(links);
+            }
+            Slot::Vacant { .. } => unreachable!("Released a vacant slot"),
+        }
+        self.free_slot = node;
+    }
+
+    fn key(&self, node: u32) -> &K {
+        match &self.slots[node as usize] {
+            Slot::Occupied { key, .. } => key,
+            Slot::Vacant { .. } => unreachable!("Vacant slots are never linked"),
+        }
+    }
+
+    fn node_height(&self, node: u32) -> usize {
+        match &self.slots[node as usize] {
+            Slot::Occupied { height, .. } => *height as usize,
+            Slot::Vacant { .. } => unreachable!("Vacant slots are never linked"),
+        }
+    }
+
+    fn link_index(&self, node: u32, level: usize) -> usize {
+        match &self.slots[node as usize] {
+            Slot::Occupied { links, height, .. } => {
+                debug_assert!(level < *height as usize);
+                *links as usize + level
+            }
+            Slot::Vacant { .. } => unreachable!("Vacant slots are never linked"),
+        }
+    }
+
+    fn next(&self, node: u32, level: usize) -> u32 {
+        if node == HEAD {
+            self.head[level]
+        } else {
+            self.links[self.link_index(node, level)]
+        }
+    }
+
+    fn set_next(&mut self, node: u32, level: usize, next: u32) {
+        if node == HEAD {
+            self.head[level] = next;
+        } else {
+            let index = self.link_index(node, level);
+            self.links[index] = next;
+        }
+    }
+}
+
+impl<K: Ord + Debug, const MAX_HEIGHT: usize, const SEED: u32> Display
+    for ArenaInner<K, MAX_HEIGHT, SEED>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "Height: {} | Size: {}\n",
+            self.height, self.size
+        ))?;
+        f.write_fmt(format_args!("[H {}], ", self.height))?;
+        let mut cur = self.head[0];
+        while cur != NIL {
+            f.write_fmt(format_args!(
+                "[{:?} {}], ",
+                self.key(cur),
+                self.node_height(cur)
+            ))?;
+            cur = self.next(cur, 0);
+        }
+        f.write_str("NIL, ")
+    }
+}
+
+impl<K: Ord + Debug, const MAX_HEIGHT: usize, const SEED: u32> Debug
+    for ArenaInner<K, MAX_HEIGHT, SEED>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{self}"))
+    }
+}
+
+#[cfg(test)]
+mod arena_test;
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+use std::sync::Barrier;
+use std::sync::Mutex;
+use std::thread::scope;
+
+use super::*;
+use crate::SkipList;
+
+concurrent_set_tests!(ArenaSkipList);
+
+#[test]
+fn clear_test() {
+    let list = ArenaSkipList::<i32>::new();
+    for i in 0..5 {
+        assert!(list.insert(i));
+    }
+
+    list.clear();
+    assert!(list.empty());
+    assert!(!list.contains(&0));
+    assert!(list.insert(0));
+}
+
+#[test]
+fn concurrent_insert_and_erase_test() {
+    let list = Arc::new(ArenaSkipList::<i32>::new());
+
+    let num_threads = 10;
+    let num_keys_per_thread = 100;
+    let successful_erasures = Arc::new(Mutex::new(0));
+    let barrier = Arc::new(Barrier::new(num_threads));
+    scope(|s| {
+        for i in 0..num_threads {
+            let list = list.clone();
+            let barrier = barrier.clone();
+            let successful_erasures = successful_erasures.clone();
+            s.spawn(move || {
+                barrier.wait();
+                let k = (i * num_keys_per_thread) as i32;
+                for j in 0..num_keys_per_thread as i32 {
+                    assert!(list.insert(k + j));
+                }
+                // Erase every other key again
+                for j in (0..num_keys_per_thread as i32).step_by(2) {
+                    if list.erase(&(k + j)) {
+                        *successful_erasures.lock().unwrap() += 1;
+                    }
+                }
+            });
+        }
+    });
+
+    let total = num_threads * num_keys_per_thread;
+    assert_eq!(*successful_erasures.lock().unwrap(), total / 2);
+    assert_eq!(list.size(), total / 2);
+    for i in 0..total as i32 {
+        assert_eq!(
+            list.contains(&i),
+            i % 2 == 1,
+            "Wrong membership for key: {}",
+            i
+        );
+    }
+}
+
+#[test]
+fn same_layout_as_skiplist_test() {
+    let arena = ArenaSkipList::<i32>::new();
+    let list = SkipList::<i32>::new();
+
+    for key in (0..200).rev().step_by(3).chain(0..100) {
+        assert_eq!(arena.insert(key), list.insert(key));
+    }
+
+    // Both draw heights from the same generator, so the towers match
+    assert_eq!(format!("{arena}"), format!("{list}"));
+}
+
+#[test]
+fn reuse_slots_test() {
+    let list = ArenaSkipList::<i32>::new();
+
+    for i in 0..1000 {
+        list.insert(i);
+    }
+    let bytes = list.allocated_bytes();
+
+    // Churn the list without growing it; freed slots and links are reused
+    for i in 0..1000 {
+        assert!(list.erase(&i));
+        assert!(list.insert(i + 1000));
+    }
+    for i in 1000..2000 {
+        assert!(list.contains(&i));
+    }
+    assert_eq!(list.size(), 1000);
+    // Towers are drawn afresh, so a few taller ones may need new links
+    assert!(list.allocated_bytes() < bytes * 2);
+
+    list.clear();
+    assert!(list.allocated_bytes() < bytes);
+}
+
+#[test]
+fn bytes_per_key_test() {
+    const LEN: i32 = 100_000;
+
+    let arena = ArenaSkipList::<i32>::new();
+    let list = SkipList::<i32>::new();
+    for key in 0..LEN {
+        arena.insert(key);
+        list.insert(key);
+    }
+
+    let arena_bytes = arena.allocated_bytes() as f64 / LEN as f64;
+    let list_bytes = list.allocated_bytes() as f64 / LEN as f64;
+    // The type docs quote about 26 and 125 bytes per key
+    assert!(arena_bytes < 30.0, "{arena_bytes:.1} bytes per key");
+    let ratio = list_bytes / arena_bytes;
+    assert!(ratio > 4.0, "SkipList takes only {ratio:.1}x the bytes");
+}
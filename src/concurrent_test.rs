@@ -0,0 +1,309 @@
+//! The concurrent set suite shared by [`LockFreeSkipList`](crate::LockFreeSkipList),
+//! [`LazySkipList`](crate::LazySkipList) and [`ArenaSkipList`](crate::ArenaSkipList).
+
+/// Expands to a `concurrent` test module that runs the suite against
+/// `$list`, which must be in scope where the macro is invoked.
+macro_rules! concurrent_set_tests {
+    ($list:ident) => {
+        mod concurrent {
+            use std::sync::Arc;
+            use std::sync::Barrier;
+            use std::sync::Mutex;
+            use std::thread::scope;
+
+            use super::$list;
+
+            #[test]
+            fn insert_contain_test() {
+                let list = $list::<i32>::new();
+
+                assert_eq!(list.size(), 0);
+                assert!(list.empty());
+
+                for i in 0..10 {
+                    assert!(list.insert(i));
+                }
+
+                for i in 0..10 {
+                    assert!(list.contains(&i));
+                    assert!(!list.insert(i));
+                }
+
+                for i in 10..20 {
+                    assert!(!list.contains(&i));
+                }
+
+                assert_eq!(list.size(), 10);
+                assert!(!list.empty());
+            }
+
+            #[test]
+            fn duplicate_insert_keeps_towers_test() {
+                let list = $list::<i32>::new();
+                let with_duplicates = $list::<i32>::new();
+                for i in 0..100 {
+                    list.insert(i);
+                    with_duplicates.insert(i);
+                    // Rejected duplicates must not draw a height
+                    assert!(!with_duplicates.insert(i / 2));
+                }
+
+                assert_eq!(format!("{with_duplicates}"), format!("{list}"));
+            }
+
+            #[test]
+            fn insert_and_erase() {
+                let list = $list::<i32>::new();
+
+                for i in 0..5 {
+                    assert!(list.insert(i));
+                }
+
+                assert!(!list.erase(&10));
+                assert_eq!(list.size(), 5);
+
+                for i in 0..5 {
+                    assert!(list.contains(&i));
+                    assert!(list.erase(&i));
+                    assert!(!list.erase(&i));
+                    assert!(!list.contains(&i));
+
+                    assert_eq!(list.size(), (5 - i - 1) as usize);
+                }
+
+                assert!(list.empty());
+
+                for i in 0..5 {
+                    assert!(list.insert(i));
+                }
+                assert_eq!(list.size(), 5);
+            }
+
+            #[test]
+            fn concurrent_insert_test() {
+                let list = $list::<i32>::new();
+
+                let num_threads = 10;
+                let num_insertions_per_thread = 100;
+
+                let successful_insertion = Arc::new(Mutex::new(0));
+
+                let list = Arc::new(list);
+                let barrier = Arc::new(Barrier::new(num_threads));
+                std::thread::scope(|s| {
+                    let mut threads = Vec::with_capacity(num_threads);
+                    for i in 0..num_threads {
+                        let list = Arc::clone(&list);
+                        let barrier = Arc::clone(&barrier);
+                        let successful_insertion = Arc::clone(&successful_insertion);
+                        let handler = s.spawn(move || {
+                            barrier.wait();
+                            let k = i * num_insertions_per_thread;
+                            for j in 0..num_insertions_per_thread {
+                                let key = k + j;
+                                if list.insert(key.try_into().unwrap()) {
+                                    let mut successful_insertion =
+                                        successful_insertion.lock().unwrap();
+                                    *successful_insertion += 1;
+                                }
+                            }
+                        });
+                        threads.push(handler);
+                    }
+                    for thread in threads {
+                        thread.join().unwrap();
+                    }
+                });
+
+                assert_eq!(
+                    successful_insertion.lock().unwrap().to_owned(),
+                    num_threads * num_insertions_per_thread
+                );
+
+                println!("{list}");
+                for i in 0..(num_threads * num_insertions_per_thread) {
+                    assert!(list.contains(&(i as i32)), "Failed to find key: {}", i);
+                }
+            }
+
+            #[test]
+            fn concurrent_erase_test() {
+                let list = Arc::new($list::<i32>::new());
+
+                for i in 0..100 {
+                    list.insert(i);
+                }
+
+                let num_threads = 10;
+                let num_erasures_per_thread = 10;
+                let successful_erasures = Arc::new(Mutex::new(0));
+                let barrier = Arc::new(Barrier::new(num_threads));
+                scope(|s| {
+                    for i in 0..num_threads {
+                        let list = list.clone();
+                        let barrier = barrier.clone();
+                        let k = i * num_erasures_per_thread;
+                        let successful_erasures = successful_erasures.clone();
+                        s.spawn(move || {
+                            barrier.wait();
+                            for j in 0..num_erasures_per_thread {
+                                if list.erase(&i32::try_from(k + j).unwrap()) {
+                                    let mut success_erase = successful_erasures.lock().unwrap();
+                                    *success_erase += 1;
+                                }
+                            }
+                        });
+                    }
+                });
+
+                assert!(successful_erasures
+                    .lock()
+                    .map(|n| *n == num_erasures_per_thread * num_threads)
+                    .unwrap());
+
+                for i in 0..100 {
+                    if i < num_threads * num_erasures_per_thread {
+                        assert!(!list.contains(&i32::try_from(i).unwrap()));
+                    } else {
+                        assert!(list.contains(&i32::try_from(i).unwrap()));
+                    }
+                }
+            }
+
+            #[test]
+            fn concurrent_insert_and_erase_test() {
+                let list = Arc::new($list::<i32>::new());
+
+                for i in 0..100 {
+                    list.insert(i);
+                }
+
+                const NUM_THREADS: i32 = 10;
+                const NUM_OPERATIONS_PER_THREAD: i32 = 10;
+
+                let succ_inserts = Arc::new(Mutex::new(0));
+                let succ_erases = Arc::new(Mutex::new(0));
+                let barrier = Arc::new(Barrier::new(NUM_THREADS as usize));
+
+                scope(|s| {
+                    for i in 0..NUM_THREADS {
+                        let barrier = Arc::clone(&barrier);
+                        let list = Arc::clone(&list);
+                        let succ_inserts = Arc::clone(&succ_inserts);
+                        let succ_erases = Arc::clone(&succ_erases);
+                        let start = i * NUM_OPERATIONS_PER_THREAD;
+                        s.spawn(move || {
+                            barrier.wait();
+                            for j in start..(NUM_OPERATIONS_PER_THREAD + start) {
+                                if !list.contains(&j) {
+                                    list.insert(j);
+                                }
+
+                                if list.insert(j + 100) {
+                                    let mut inserts = succ_inserts.lock().unwrap();
+                                    *inserts += 1;
+                                }
+
+                                if list.erase(&j) {
+                                    let mut erases = succ_erases.lock().unwrap();
+                                    *erases += 1;
+                                }
+                            }
+                        });
+                    }
+                });
+
+                assert!(succ_inserts
+                    .lock()
+                    .map(|n| *n == NUM_THREADS * NUM_OPERATIONS_PER_THREAD)
+                    .unwrap());
+                assert!(succ_erases
+                    .lock()
+                    .map(|n| *n == NUM_THREADS * NUM_OPERATIONS_PER_THREAD)
+                    .unwrap());
+
+                for i in 100..(100 + NUM_THREADS * NUM_OPERATIONS_PER_THREAD) {
+                    assert!(list.contains(&i));
+                }
+
+                for i in 0..(NUM_THREADS * NUM_OPERATIONS_PER_THREAD) {
+                    assert!(!list.contains(&i));
+                }
+            }
+
+            #[test]
+            fn concurrent_read_test() {
+                const NUM_THREADS: usize = 8;
+
+                const TOTAL_NUM_ELEMENTS: usize = NUM_THREADS * 100000;
+
+                let list = Arc::new($list::<i32>::new());
+                for i in 0..TOTAL_NUM_ELEMENTS {
+                    list.insert(i as i32);
+                }
+                let barrier = Arc::new(Barrier::new(NUM_THREADS));
+
+                scope(|s| {
+                    for _ in 0..NUM_THREADS {
+                        let list = Arc::clone(&list);
+                        let barrier = Arc::clone(&barrier);
+                        s.spawn(move || {
+                            barrier.wait();
+                            for i in 0..TOTAL_NUM_ELEMENTS {
+                                list.contains(&(i as i32));
+                            }
+                        });
+                    }
+                });
+            }
+
+            #[test]
+            fn contended_insert_erase_test() {
+                const NUM_THREADS: usize = 8;
+                const NUM_KEYS: i32 = 64;
+                const ROUNDS: usize = 2000;
+
+                let list = Arc::new($list::<i32>::new());
+                let net = Arc::new(Mutex::new(vec![0i64; NUM_KEYS as usize]));
+                let barrier = Arc::new(Barrier::new(NUM_THREADS));
+
+                // Every thread hammers the same small key space
+                scope(|s| {
+                    for t in 0..NUM_THREADS {
+                        let list = Arc::clone(&list);
+                        let net = Arc::clone(&net);
+                        let barrier = Arc::clone(&barrier);
+                        s.spawn(move || {
+                            let mut local = vec![0i64; NUM_KEYS as usize];
+                            barrier.wait();
+                            for round in 0..ROUNDS {
+                                let key = ((round * 7 + t * 13) % NUM_KEYS as usize) as i32;
+                                if list.insert(key) {
+                                    local[key as usize] += 1;
+                                }
+                                if list.erase(&((key + 5) % NUM_KEYS)) {
+                                    local[((key + 5) % NUM_KEYS) as usize] -= 1;
+                                }
+                            }
+                            let mut net = net.lock().unwrap();
+                            for (total, delta) in net.iter_mut().zip(local) {
+                                *total += delta;
+                            }
+                        });
+                    }
+                });
+
+                // Each key was inserted at most once more than it was erased
+                let net = net.lock().unwrap();
+                let mut expected_size = 0;
+                for key in 0..NUM_KEYS {
+                    let count = net[key as usize];
+                    assert!(count == 0 || count == 1, "key {key} has net count {count}");
+                    assert_eq!(list.contains(&key), count == 1, "key {key}");
+                    expected_size += count as usize;
+                }
+                assert_eq!(list.size(), expected_size);
+            }
+        }
+    };
+}
@@ -0,0 +1,365 @@
+use std::{
+    array,
+    borrow::Borrow,
+    fmt::{Debug, Display},
+    hint,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, RwLock, RwLockWriteGuard,
+    },
+    thread,
+};
+
+use crate::skiplist::{AtomicSplitMix, HeightGenerator, RngHeights};
+
+/// A concurrent ordered set backed by a lazy skiplist.
+///
+/// Unlike [`SkipList`](crate::SkipList), there is no lock over the whole
+/// list: every node guards its own links. Searches only ever hold one read
+/// lock at a time, and writers lock just the predecessors they are about to
+/// relink, then check that nothing changed since their search (the
+/// optimistic part). Inserts and erases on disjoint parts of the list
+/// therefore run in parallel. Tower heights come from a SplitMix64
+/// generator seeded with `SEED` and kept in one atomic, so drawing them
+/// does not serialize inserts either.
+///
+/// Removal is lazy: a node is first marked, which takes it out of the set,
+/// and then unlinked. A node only counts as present once it is linked on
+/// every level of its tower.
+///
+/// ```
+/// use p0::LazySkipList;
+///
+/// let list = LazySkipList::<i32>::new();
+/// assert!(list.insert(1));
+/// assert!(!list.insert(1));
+/// assert!(list.contains(&1));
+/// assert!(list.erase(&1));
+/// assert!(list.empty());
+/// ```
+pub struct LazySkipList<K, const MAX_HEIGHT: usize = 14, const SEED: u32 = 15445> {
+    header: Arc<Node<K>>,
+    size: AtomicUsize,
+    rng: AtomicSplitMix,
+}
+
+type Link<K> = Option<Arc<Node<K>>>;
+
+struct Node<K> {
+    /// `None` only for the header.
+    key: Option<K>,
+    height: usize,
+    /// Set once the node is logically removed.
+    marked: AtomicBool,
+    /// Set once the node is linked on every level of its tower.
+    fully_linked: AtomicBool,
+    /// The node lock. Writers hold it to relink the node's successors.
+    links: RwLock<Vec<Link<K>>>,
+}
+
+type LinksGuard<'a, K> = RwLockWriteGuard<'a, Vec<Link<K>>>;
+
+impl<K> Node<K> {
+    /// Clears the node's links and returns its level-0 successor.
+    fn unlink(&self) -> Link<K> {
+        let mut links = self.links.write().unwrap();
+        let next = links.first_mut().and_then(Option::take);
+        links.clear();
+        next
+    }
+}
+
+impl<K: Ord> Node<K> {
+    fn is_before<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.key
+            .as_ref()
+            .is_none_or(|node_key| node_key.borrow() < key)
+    }
+
+    fn next(&self, level: usize) -> Link<K> {
+        self.links.read().unwrap()[level].clone()
+    }
+}
+
+fn same_node<K>(a: &Link<K>, b: &Link<K>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+impl<K: Ord, const MAX_HEIGHT: usize, const SEED: u32> LazySkipList<K, MAX_HEIGHT, SEED> {
+    /// Creates an empty list.
+    pub fn new() -> Self {
+        LazySkipList {
+            header: Arc::new(Node {
+                key: None,
+                height: MAX_HEIGHT,
+                marked: AtomicBool::new(false),
+                fully_linked: AtomicBool::new(true),
+                links: RwLock::new(vec![None; MAX_HEIGHT]),
+            }),
+            size: AtomicUsize::new(0),
+            rng: AtomicSplitMix::new(SEED),
+        }
+    }
+
+    /// Returns `true` if the list holds no keys.
+    pub fn empty(&self) -> bool {
+        self.size() == 0
+    }
+
+    /// Returns the number of keys in the list.
+    pub fn size(&self) -> usize {
+        self.size.load(Ordering::Acquire)
+    }
+
+    /// Inserts `key`, returning `false` if it was already present.
+    pub fn insert(&self, key: K) -> bool {
+        let mut drawn = None;
+        let mut preds: [Arc<Node<K>>; MAX_HEIGHT] = array::from_fn(|_| self.header.clone());
+        let mut succs: [Link<K>; MAX_HEIGHT] = array::from_fn(|_| None);
+
+        loop {
+            if let Some(level) = self.find(&key, &mut preds, &mut succs) {
+                let found = succs[level]
+                    .as_ref()
+                    .expect("Found node should be a successor");
+                if !found.marked.load(Ordering::Acquire) {
+                    // Another insert of the same key is still linking its tower
+                    while !found.fully_linked.load(Ordering::Acquire) {
+                        hint::spin_loop();
+                    }
+                    return false;
+                }
+                // Wait for the eraser to unlink the marked node, without
+                // taking read locks out from under it
+                thread::yield_now();
+                continue;
+            }
+            // Drawn on the first miss only: a retry after the locked
+            // predecessors fail validation reuses it
+            let height = *drawn.get_or_insert_with(|| self.random_height());
+
+            let Some(mut guards) = self.lock_preds(&preds, height, |level, pred, links| {
+                !pred.marked.load(Ordering::Acquire)
+                    && succs[level]
+                        .as_ref()
+                        .is_none_or(|succ| !succ.marked.load(Ordering::Acquire))
+                    && same_node(&links[level], &succs[level])
+            }) else {
+                continue;
+            };
+
+            let new_node = Arc::new(Node {
+                key: Some(key),
+                height,
+                marked: AtomicBool::new(false),
+                fully_linked: AtomicBool::new(false),
+                links: RwLock::new(succs[..height].to_vec()),
+            });
+            let mut guard = 0;
+            for level in 0..height {
+                if level > 0 && !Arc::ptr_eq(&preds[level], &preds[level - 1]) {
+                    guard += 1;
+                }
+                guards[guard][level] = Some(new_node.clone());
+            }
+            new_node.fully_linked.store(true, Ordering::Release);
+            self.size.fetch_add(1, Ordering::AcqRel);
+
+            return true;
+        }
+    }
+
+    /// Removes `key`, returning `false` if it was not present.
+    pub fn erase<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut preds: [Arc<Node<K>>; MAX_HEIGHT] = array::from_fn(|_| self.header.clone());
+        let mut succs: [Link<K>; MAX_HEIGHT] = array::from_fn(|_| None);
+
+        let Some(level) = self.find(key, &mut preds, &mut succs) else {
+            return false;
+        };
+        let victim = succs[level]
+            .clone()
+            .expect("Found node should be a successor");
+        // A node that is still being linked or already erased is not in the set
+        if !victim.fully_linked.load(Ordering::Acquire)
+            || victim.height != level + 1
+            || victim.marked.load(Ordering::Acquire)
+        {
+            return false;
+        }
+
+        // Marking under a read lock on the victim waits out writers relinking
+        // its successors. Those that come later find it marked and back off,
+        // so its links are final and the lock can go before the unlink.
+        let victim_links = {
+            let links = victim.links.read().unwrap();
+            if victim.marked.swap(true, Ordering::AcqRel) {
+                return false;
+            }
+            links.clone()
+        };
+        self.size.fetch_sub(1, Ordering::AcqRel);
+
+        loop {
+            let Some(mut guards) = self.lock_preds(&preds, victim.height, |level, pred, links| {
+                !pred.marked.load(Ordering::Acquire)
+                    && links[level]
+                        .as_ref()
+                        .is_some_and(|next| Arc::ptr_eq(next, &victim))
+            }) else {
+                self.find(key, &mut preds, &mut succs);
+                continue;
+            };
+
+            let mut guard = guards.len() - 1;
+            for level in (0..victim.height).rev() {
+                if level + 1 < victim.height && !Arc::ptr_eq(&preds[level], &preds[level + 1]) {
+                    guard -= 1;
+                }
+                guards[guard][level] = victim_links[level].clone();
+            }
+
+            return true;
+        }
+    }
+
+    /// Returns `true` if `key` is in the list.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut preds: [Arc<Node<K>>; MAX_HEIGHT] = array::from_fn(|_| self.header.clone());
+        let mut succs: [Link<K>; MAX_HEIGHT] = array::from_fn(|_| None);
+        match self.find(key, &mut preds, &mut succs) {
+            Some(level) => succs[level].as_ref().is_some_and(|node| {
+                node.fully_linked.load(Ordering::Acquire) && !node.marked.load(Ordering::Acquire)
+            }),
+            None => false,
+        }
+    }
+
+    /// Fills `preds` and `succs` with the nodes around `key` on every level
+    /// and returns the highest level on which `key` was found. Only one node
+    /// lock is held at any time.
+    fn find<Q>(
+        &self,
+        key: &Q,
+        preds: &mut [Arc<Node<K>>; MAX_HEIGHT],
+        succs: &mut [Link<K>; MAX_HEIGHT],
+    ) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut found = None;
+        let mut pred = self.header.clone();
+        for level in (0..MAX_HEIGHT).rev() {
+            let mut cur = pred.next(level);
+            while let Some(node) = cur.take_if(|node| node.is_before(key)) {
+                cur = node.next(level);
+                pred = node;
+            }
+            let is_key = |node: &Arc<Node<K>>| node.key.as_ref().is_some_and(|k| k.borrow() == key);
+            if found.is_none() && cur.as_ref().is_some_and(is_key) {
+                found = Some(level);
+            }
+            preds[level] = pred.clone();
+            succs[level] = cur;
+        }
+        found
+    }
+
+    /// Write-locks the distinct predecessors on levels `0..height`, bottom
+    /// up, and checks `valid` on every level. Returns the guards in locking
+    /// order, or `None` if a check failed.
+    ///
+    /// Predecessors on higher levels never have greater keys, so every
+    /// writer takes node locks in descending key order and cannot deadlock.
+    fn lock_preds<'a>(
+        &self,
+        preds: &'a [Arc<Node<K>>; MAX_HEIGHT],
+        height: usize,
+        valid: impl Fn(usize, &Node<K>, &[Link<K>]) -> bool,
+    ) -> Option<Vec<LinksGuard<'a, K>>> {
+        let mut guards: Vec<LinksGuard<'a, K>> = Vec::with_capacity(height);
+        for level in 0..height {
+            if level == 0 || !Arc::ptr_eq(&preds[level], &preds[level - 1]) {
+                guards.push(preds[level].links.write().unwrap());
+            }
+            let links = guards.last().expect("A predecessor was just locked");
+            if !valid(level, &preds[level], links) {
+                return None;
+            }
+        }
+        Some(guards)
+    }
+
+    fn random_height(&self) -> usize {
+        RngHeights::new(&self.rng).next_height(MAX_HEIGHT)
+    }
+}
+
+impl<K: Ord, const MAX_HEIGHT: usize, const SEED: u32> Default
+    for LazySkipList<K, MAX_HEIGHT, SEED>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, const MAX_HEIGHT: usize, const SEED: u32> Drop for LazySkipList<K, MAX_HEIGHT, SEED> {
+    fn drop(&mut self) {
+        // Clear one tower at a time so that dropping a node cannot cascade
+        // down the rest of the list
+        let mut cur = self.header.unlink();
+        while let Some(node) = cur {
+            cur = node.unlink();
+        }
+    }
+}
+
+impl<K: Debug, const MAX_HEIGHT: usize, const SEED: u32> Display
+    for LazySkipList<K, MAX_HEIGHT, SEED>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "Size: {}\n",
+            self.size.load(Ordering::Acquire)
+        ))?;
+        f.write_str("[H], ")?;
+        let mut cur = self.header.links.read().unwrap()[0].clone();
+        while let Some(node) = cur {
+            if !node.marked.load(Ordering::Acquire) {
+                if let Some(key) = &node.key {
+                    f.write_fmt(format_args!("[{:?} {}], ", key, node.height))?;
+                }
+            }
+            cur = node.links.read().unwrap()[0].clone();
+        }
+        Ok(())
+    }
+}
+
+impl<K: Debug, const MAX_HEIGHT: usize, const SEED: u32> Debug
+    for LazySkipList<K, MAX_HEIGHT, SEED>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{self}"))
+    }
+}
+
+#[cfg(test)]
+mod lazy_test;
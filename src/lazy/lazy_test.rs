@@ -0,0 +1,66 @@
+use std::sync::Arc;
+use std::thread::scope;
+
+use super::*;
+
+concurrent_set_tests!(LazySkipList);
+
+#[test]
+fn disjoint_writers_test() {
+    let list = LazySkipList::<i32>::new();
+    for i in 0..100 {
+        list.insert(i * 10);
+    }
+
+    let mut node = list.header.next(0);
+    while let Some(n) = node.take_if(|n| n.key != Some(500)) {
+        node = n.next(0);
+    }
+    let node = node.expect("500 should be in the list");
+
+    // Holding one node lock must not stop writers that never reach it
+    let node_write_lock = node.links.write().unwrap();
+    scope(|s| {
+        s.spawn(|| {
+            assert!(list.insert(5));
+            assert!(list.erase(&10));
+            assert!(list.contains(&20));
+            assert!(!list.contains(&10));
+        })
+        .join()
+        .unwrap();
+    });
+    drop(node_write_lock);
+
+    assert!(list.insert(505));
+    assert!(list.contains(&5));
+    assert!(list.contains(&505));
+    assert_eq!(list.size(), 101);
+}
+
+#[test]
+fn drop_long_list_test() {
+    const LEN: i32 = 10_000_000;
+
+    let list = LazySkipList::<i32>::new();
+    let mut last = list.header.clone();
+    for key in 0..LEN {
+        let node = Arc::new(Node {
+            key: Some(key),
+            height: 1,
+            marked: AtomicBool::new(false),
+            fully_linked: AtomicBool::new(true),
+            links: RwLock::new(vec![None]),
+        });
+        last.links.write().unwrap()[0] = Some(node.clone());
+        last = node;
+    }
+    drop(last);
+
+    std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(move || drop(list))
+        .unwrap()
+        .join()
+        .unwrap();
+}
@@ -1 +1,89 @@
-pub mod skiplist;
\ No newline at end of file
+//! Ordered in-memory collections built on skiplists.
+//!
+//! [`SkipList`] is a thread-safe ordered set and [`SkipMap`] its key-value
+//! counterpart:
+//!
+//! ```
+//! use std::sync::Arc;
+//! use std::thread;
+//!
+//! use p0::SkipList;
+//!
+//! let list = Arc::new(SkipList::<i32>::new());
+//! let handles: Vec<_> = (0..4)
+//!     .map(|t| {
+//!         let list = Arc::clone(&list);
+//!         thread::spawn(move || {
+//!             for i in 0..10 {
+//!                 list.insert(t * 10 + i);
+//!             }
+//!         })
+//!     })
+//!     .collect();
+//! for handle in handles {
+//!     handle.join().unwrap();
+//! }
+//! assert_eq!(list.size(), 40);
+//! ```
+//!
+//! The error enums, [`SkipListError`], [`ConfigError`] and
+//! [`ValidationError`], are `#[non_exhaustive]`, and so are their variants
+//! with fields: later versions may add both. Matches on them need a
+//! wildcard arm, and `..` in variant patterns:
+//!
+//! ```
+//! use p0::{SkipList, SkipListConfig, SkipListError};
+//!
+//! let list = SkipList::<i32>::with_config(SkipListConfig::new().capacity(1)).unwrap();
+//! list.insert(1);
+//! let reason = match list.try_insert(2) {
+//!     Ok(_) => "inserted".to_string(),
+//!     Err(SkipListError::CapacityExceeded { capacity, .. }) => format!("full at {capacity}"),
+//!     Err(error) => error.to_string(),
+//! };
+//! assert_eq!(reason, "full at 1");
+//! ```
+//!
+//! Leaving the wildcard out does not compile:
+//!
+//! ```compile_fail
+//! use p0::SkipListError;
+//!
+//! fn describe(error: SkipListError) -> &'static str {
+//!     match error {
+//!         SkipListError::Poisoned => "poisoned",
+//!         SkipListError::CapacityExceeded { .. } => "full",
+//!         SkipListError::Unsorted { .. } => "unsorted",
+//!     }
+//! }
+//! ```
+
+#[cfg(test)]
+#[macro_use]
+mod concurrent_test;
+
+pub mod arena;
+pub mod lazy;
+pub mod lockfree;
+pub mod memtable;
+pub mod multiset;
+pub mod skiplist;
+pub mod skipmap;
+pub mod versioned;
+
+pub use arena::ArenaSkipList;
+pub use lazy::LazySkipList;
+pub use lockfree::LockFreeSkipList;
+pub use memtable::{FrozenMemtable, Memtable, MemtableIter};
+pub use multiset::SkipMultiSet;
+pub use skiplist::{
+    Comparator, ConfigError, HeightGenerator, IntoIter, Iter, Mt19937Heights, OrdComparator, Range,
+    RngHeights, ScriptedHeights, SkipList, SkipListConfig, SkipListError, Snapshot, SnapshotIter,
+    ThreadLocalHeights, ThreadLocalRng, ValidationError,
+};
+pub use skipmap::SkipMap;
+pub use versioned::{VersionedMap, VersionedRange};
+
+/// The `rand_core` version [`RngHeights`] takes generators from, so custom
+/// generators implement the matching `RngCore`.
+pub use rand_core;
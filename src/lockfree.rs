@@ -0,0 +1,391 @@
+use std::{
+    array,
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt::{Debug, Display},
+    sync::atomic::{self, AtomicUsize},
+};
+
+use crossbeam_epoch::{self as epoch, Atomic, Guard, Owned, Shared};
+
+use crate::skiplist::{AtomicSplitMix, HeightGenerator, RngHeights};
+
+/// A lock-free ordered set backed by a skiplist.
+///
+/// Towers are linked with compare-and-swap and never take a lock, so
+/// writers on disjoint keys proceed in parallel. A node is removed by first
+/// marking its links (logical removal) and then unlinking it level by level;
+/// any search that runs into a marked node helps unlink it. Removed nodes
+/// are reclaimed through `crossbeam-epoch` once no thread can still be
+/// reading them.
+///
+/// The API mirrors [`SkipList`](crate::SkipList). Tower heights come from a
+/// SplitMix64 generator seeded with `SEED` whose state is a single atomic,
+/// so drawing them takes no lock either.
+///
+/// ```
+/// use p0::LockFreeSkipList;
+///
+/// let list = LockFreeSkipList::<i32>::new();
+/// assert!(list.insert(1));
+/// assert!(!list.insert(1));
+/// assert!(list.contains(&1));
+/// assert!(list.erase(&1));
+/// assert!(list.empty());
+/// ```
+pub struct LockFreeSkipList<K, const MAX_HEIGHT: usize = 14, const SEED: u32 = 15445> {
+    head: [Atomic<Node<K>>; MAX_HEIGHT],
+    size: AtomicUsize,
+    rng: AtomicSplitMix,
+}
+
+struct Node<K> {
+    key: K,
+    /// Successor on each level. A tag of 1 marks the node as removed from
+    /// that level.
+    tower: Box<[Atomic<Node<K>>]>,
+    /// Held once by the list and once by the inserter while it links the
+    /// upper levels. Whoever drops the last one retires the node.
+    refs: AtomicUsize,
+}
+
+type Tower<K> = [Atomic<Node<K>>];
+
+impl<K: Ord + Send + 'static, const MAX_HEIGHT: usize, const SEED: u32>
+    LockFreeSkipList<K, MAX_HEIGHT, SEED>
+{
+    /// Creates an empty list.
+    pub fn new() -> Self {
+        LockFreeSkipList {
+            head: array::from_fn(|_| Atomic::null()),
+            size: AtomicUsize::new(0),
+            rng: AtomicSplitMix::new(SEED),
+        }
+    }
+
+    /// Returns `true` if the list holds no keys.
+    pub fn empty(&self) -> bool {
+        self.size() == 0
+    }
+
+    /// Returns the number of keys in the list.
+    ///
+    /// The count is updated after each insert or erase takes effect, so it
+    /// may briefly lag behind concurrent writers.
+    pub fn size(&self) -> usize {
+        self.size.load(atomic::Ordering::Acquire)
+    }
+
+    /// Inserts `key`, returning `false` if it was already present.
+    pub fn insert(&self, key: K) -> bool {
+        let guard = &epoch::pin();
+        let mut preds = [&self.head[..]; MAX_HEIGHT];
+        let mut succs = [Shared::null(); MAX_HEIGHT];
+
+        if self.search(&key, &mut preds, &mut succs, guard) {
+            return false;
+        }
+        // The search above found no such key; the height is drawn here, once,
+        // and the node keeps it through every failed CAS below
+        let height = self.random_height();
+        let mut new_node = Owned::new(Node {
+            key,
+            tower: (0..height).map(|_| Atomic::null()).collect(),
+            refs: AtomicUsize::new(2),
+        });
+
+        // Linking level 0 is what makes the key part of the set
+        let node = loop {
+            for (level, link) in new_node.tower.iter().enumerate() {
+                link.store(succs[level], atomic::Ordering::Relaxed);
+            }
+            match preds[0][0].compare_exchange(
+                succs[0],
+                new_node,
+                atomic::Ordering::AcqRel,
+                atomic::Ordering::Acquire,
+                guard,
+            ) {
+                Ok(node) => break node,
+                Err(e) => new_node = e.new,
+            }
+            if self.search(&new_node.key, &mut preds, &mut succs, guard) {
+                return false;
+            }
+        };
+        self.size.fetch_add(1, atomic::Ordering::AcqRel);
+
+        // SAFETY: the node cannot be retired while we still hold a reference
+        let node_ref = unsafe { node.deref() };
+        'levels: for level in 1..height {
+            loop {
+                let next = node_ref.tower[level].load(atomic::Ordering::Acquire, guard);
+                if next.tag() == 1 {
+                    // Erased while being built, stop growing the tower
+                    break 'levels;
+                }
+                if next != succs[level]
+                    && node_ref.tower[level]
+                        .compare_exchange(
+                            next,
+                            succs[level],
+                            atomic::Ordering::AcqRel,
+                            atomic::Ordering::Acquire,
+                            guard,
+                        )
+                        .is_err()
+                {
+                    continue;
+                }
+                if preds[level][level]
+                    .compare_exchange(
+                        succs[level],
+                        node,
+                        atomic::Ordering::AcqRel,
+                        atomic::Ordering::Acquire,
+                        guard,
+                    )
+                    .is_ok()
+                {
+                    break;
+                }
+                self.search(&node_ref.key, &mut preds, &mut succs, guard);
+            }
+        }
+        self.release(node, guard);
+
+        true
+    }
+
+    /// Removes `key`, returning `false` if it was not present.
+    pub fn erase<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let guard = &epoch::pin();
+        let mut preds = [&self.head[..]; MAX_HEIGHT];
+        let mut succs = [Shared::null(); MAX_HEIGHT];
+
+        if !self.search(key, &mut preds, &mut succs, guard) {
+            return false;
+        }
+        let node = succs[0];
+        // SAFETY: protected by the guard
+        let node_ref = unsafe { node.deref() };
+
+        // Mark the upper levels first so that no search can reach the node
+        // from above once it is gone from level 0
+        for link in node_ref.tower[1..].iter().rev() {
+            let mut next = link.load(atomic::Ordering::Acquire, guard);
+            while next.tag() == 0 {
+                match link.compare_exchange(
+                    next,
+                    next.with_tag(1),
+                    atomic::Ordering::AcqRel,
+                    atomic::Ordering::Acquire,
+                    guard,
+                ) {
+                    Ok(_) => break,
+                    Err(e) => next = e.current,
+                }
+            }
+        }
+
+        // Marking level 0 decides which eraser removes the key
+        let mut next = node_ref.tower[0].load(atomic::Ordering::Acquire, guard);
+        loop {
+            if next.tag() == 1 {
+                return false;
+            }
+            match node_ref.tower[0].compare_exchange(
+                next,
+                next.with_tag(1),
+                atomic::Ordering::AcqRel,
+                atomic::Ordering::Acquire,
+                guard,
+            ) {
+                Ok(_) => break,
+                Err(e) => next = e.current,
+            }
+        }
+        self.size.fetch_sub(1, atomic::Ordering::AcqRel);
+
+        self.search(key, &mut preds, &mut succs, guard);
+        self.release(node, guard);
+        true
+    }
+
+    /// Returns `true` if `key` is in the list.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let guard = &epoch::pin();
+        let mut pred = &self.head[..];
+        for level in (0..MAX_HEIGHT).rev() {
+            let mut cur = pred[level].load(atomic::Ordering::Acquire, guard);
+            // SAFETY: protected by the guard
+            while let Some(cur_ref) = unsafe { cur.as_ref() } {
+                let next = cur_ref.tower[level]
+                    .load(atomic::Ordering::Acquire, guard)
+                    .with_tag(0);
+                match cur_ref.key.borrow().cmp(key) {
+                    Ordering::Less => {
+                        pred = &cur_ref.tower;
+                        cur = next;
+                    }
+                    Ordering::Equal
+                        if cur_ref.tower[0]
+                            .load(atomic::Ordering::Acquire, guard)
+                            .tag()
+                            == 0 =>
+                    {
+                        return true;
+                    }
+                    // An erased node may still precede a live one with the
+                    // same key
+                    Ordering::Equal => cur = next,
+                    Ordering::Greater => break,
+                }
+            }
+        }
+        false
+    }
+
+    /// Fills `preds` and `succs` with the nodes around `key` on every level,
+    /// unlinking marked nodes along the way, and returns `true` if `key` is
+    /// present.
+    fn search<'g, Q>(
+        &'g self,
+        key: &Q,
+        preds: &mut [&'g Tower<K>; MAX_HEIGHT],
+        succs: &mut [Shared<'g, Node<K>>; MAX_HEIGHT],
+        guard: &'g Guard,
+    ) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        'retry: loop {
+            let mut pred = &self.head[..];
+            for level in (0..MAX_HEIGHT).rev() {
+                let mut cur = pred[level]
+                    .load(atomic::Ordering::Acquire, guard)
+                    .with_tag(0);
+                // SAFETY: protected by the guard
+                while let Some(cur_ref) = unsafe { cur.as_ref() } {
+                    let next = cur_ref.tower[level].load(atomic::Ordering::Acquire, guard);
+                    if next.tag() == 1 {
+                        // `cur` is being erased, help unlink it from this level
+                        match pred[level].compare_exchange(
+                            cur,
+                            next.with_tag(0),
+                            atomic::Ordering::AcqRel,
+                            atomic::Ordering::Acquire,
+                            guard,
+                        ) {
+                            Ok(_) => {
+                                cur = next.with_tag(0);
+                                continue;
+                            }
+                            Err(_) => continue 'retry,
+                        }
+                    }
+                    if cur_ref.key.borrow() < key {
+                        pred = &cur_ref.tower;
+                        cur = next;
+                    } else {
+                        break;
+                    }
+                }
+                preds[level] = pred;
+                succs[level] = cur;
+            }
+            // SAFETY: protected by the guard
+            return unsafe { succs[0].as_ref() }.is_some_and(|node| node.key.borrow() == key);
+        }
+    }
+
+    /// Drops one reference to `node`, retiring it if that was the last one.
+    fn release(&self, node: Shared<'_, Node<K>>, guard: &Guard) {
+        // SAFETY: the caller still holds one of the references
+        let node_ref = unsafe { node.deref() };
+        if node_ref.refs.fetch_sub(1, atomic::Ordering::AcqRel) != 1 {
+            return;
+        }
+        // The inserter and the eraser are both done with the node, so no
+        // level can be linked anymore. One last search unlinks any level
+        // that the eraser's own search ran ahead of.
+        let mut preds = [&self.head[..]; MAX_HEIGHT];
+        let mut succs = [Shared::null(); MAX_HEIGHT];
+        self.search(&node_ref.key, &mut preds, &mut succs, guard);
+        // SAFETY: the node is unreachable, and threads that still see it are
+        // pinned
+        unsafe { guard.defer_destroy(node) };
+    }
+
+    fn random_height(&self) -> usize {
+        RngHeights::new(&self.rng).next_height(MAX_HEIGHT)
+    }
+}
+
+impl<K: Ord + Send + 'static, const MAX_HEIGHT: usize, const SEED: u32> Default
+    for LockFreeSkipList<K, MAX_HEIGHT, SEED>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, const MAX_HEIGHT: usize, const SEED: u32> Drop for LockFreeSkipList<K, MAX_HEIGHT, SEED> {
+    fn drop(&mut self) {
+        // SAFETY: `&mut self` rules out concurrent access, and erased nodes
+        // were already handed to the collector
+        unsafe {
+            let guard = epoch::unprotected();
+            let mut cur = self.head[0].load(atomic::Ordering::Relaxed, guard);
+            while !cur.is_null() {
+                let node = cur.into_owned();
+                cur = node.tower[0]
+                    .load(atomic::Ordering::Relaxed, guard)
+                    .with_tag(0);
+            }
+        }
+    }
+}
+
+impl<K: Debug, const MAX_HEIGHT: usize, const SEED: u32> Display
+    for LockFreeSkipList<K, MAX_HEIGHT, SEED>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "Size: {}\n",
+            self.size.load(atomic::Ordering::Acquire)
+        ))?;
+        let guard = &epoch::pin();
+        f.write_str("[H], ")?;
+        let mut cur = self.head[0].load(atomic::Ordering::Acquire, guard);
+        // SAFETY: protected by the guard
+        while let Some(node) = unsafe { cur.as_ref() } {
+            let next = node.tower[0].load(atomic::Ordering::Acquire, guard);
+            if next.tag() == 0 {
+                f.write_fmt(format_args!("[{:?} {}], ", node.key, node.tower.len()))?;
+            }
+            cur = next.with_tag(0);
+        }
+        Ok(())
+    }
+}
+
+impl<K: Debug, const MAX_HEIGHT: usize, const SEED: u32> Debug
+    for LockFreeSkipList<K, MAX_HEIGHT, SEED>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{self}"))
+    }
+}
+
+#[cfg(test)]
+mod lockfree_test;
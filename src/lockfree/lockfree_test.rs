@@ -0,0 +1,3 @@
+use super::*;
+
+concurrent_set_tests!(LockFreeSkipList);
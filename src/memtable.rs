@@ -0,0 +1,282 @@
+use std::{
+    borrow::Borrow,
+    fmt::{Debug, Display},
+    iter::FusedIterator,
+    mem,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        RwLock, RwLockWriteGuard,
+    },
+};
+
+use crate::skiplist::{Cursor, IntoIter, OrdComparator, SkipListInner};
+
+/// The size limit of [`Memtable::new`], LevelDB's default write buffer.
+pub const DEFAULT_LIMIT: usize = 4 << 20;
+
+/// The mutable in-memory table of a log-structured merge tree.
+///
+/// Writes land in a key-value skiplist, where a deletion is stored as a
+/// tombstone so that it can shadow older versions of the key further down
+/// the tree. The table counts the bytes of its keys and values, and once it
+/// reaches its limit, [`freeze`](Self::freeze) hands its contents over as a
+/// read-only [`FrozenMemtable`] to flush, while the memtable itself starts
+/// over empty and keeps taking writes. Locking works as in
+/// [`SkipMap`](crate::SkipMap).
+///
+/// ```
+/// use p0::Memtable;
+///
+/// let memtable = Memtable::<Vec<u8>, Vec<u8>>::with_limit(16);
+/// memtable.put(b"apple".to_vec(), b"red".to_vec());
+/// memtable.delete(b"pear".to_vec());
+/// assert_eq!(memtable.get(&b"apple"[..]), Some(Some(b"red".to_vec())));
+/// assert_eq!(memtable.get(&b"pear"[..]), Some(None));
+/// assert_eq!(memtable.get(&b"plum"[..]), None);
+/// assert_eq!(memtable.approximate_bytes(), 12);
+/// assert!(memtable.freeze_if_full().is_none());
+///
+/// memtable.put(b"banana".to_vec(), b"yellow".to_vec());
+/// let frozen = memtable.freeze_if_full().unwrap();
+/// assert!(memtable.is_empty());
+/// let flushed: Vec<_> = frozen.into_iter().map(|(key, _)| key).collect();
+/// assert_eq!(flushed, vec![b"apple".to_vec(), b"banana".to_vec(), b"pear".to_vec()]);
+/// ```
+pub struct Memtable<K: Ord, V, const MAX_HEIGHT: usize = 14, const SEED: u32 = 15445> {
+    inner: RwLock<SkipListInner<K, Option<V>, MAX_HEIGHT, SEED>>,
+    /// Bytes of the keys and values in `inner`. Only stored to under its
+    /// write lock, once per write, so lock-free readers never see a
+    /// half-applied write.
+    bytes: AtomicUsize,
+    limit: usize,
+}
+
+impl<K: Ord + AsRef<[u8]>, V: AsRef<[u8]>, const MAX_HEIGHT: usize, const SEED: u32>
+    Memtable<K, V, MAX_HEIGHT, SEED>
+{
+    /// Creates an empty memtable that is full at [`DEFAULT_LIMIT`] bytes.
+    pub fn new() -> Self {
+        Self::with_limit(DEFAULT_LIMIT)
+    }
+
+    /// Creates an empty memtable that is full once its keys and values take
+    /// `limit` bytes.
+    pub fn with_limit(limit: usize) -> Self {
+        Memtable {
+            inner: RwLock::new(SkipListInner::new()),
+            bytes: AtomicUsize::new(0),
+            limit,
+        }
+    }
+
+    /// Returns `true` if the memtable holds no entries, tombstones included.
+    pub fn is_empty(&self) -> bool {
+        self.inner.read().unwrap().empty()
+    }
+
+    /// Returns the number of entries, tombstones included.
+    pub fn size(&self) -> usize {
+        self.inner.read().unwrap().size()
+    }
+
+    /// Returns the bytes taken by the keys and values, not counting the
+    /// skiplist's own nodes. A tombstone counts its key only.
+    pub fn approximate_bytes(&self) -> usize {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` once the keys and values take at least the limit.
+    pub fn is_full(&self) -> bool {
+        self.approximate_bytes() >= self.limit
+    }
+
+    /// Looks up `key`. Returns `Some(None)` if it was deleted, so callers
+    /// know not to search older tables.
+    pub fn get<Q>(&self, key: &Q) -> Option<Option<V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        V: Clone,
+    {
+        lookup(&self.inner.read().unwrap(), key)
+    }
+
+    /// Hands the current entries over as a [`FrozenMemtable`] and leaves the
+    /// memtable empty, to take further writes. Concurrent writes land either
+    /// in the frozen table or in the emptied one, never in both.
+    pub fn freeze(&self) -> FrozenMemtable<K, V, MAX_HEIGHT, SEED> {
+        self.freeze_locked(self.inner.write().unwrap())
+    }
+
+    /// Like [`freeze`](Self::freeze), but only if the memtable
+    /// [is full](Self::is_full). Checking and freezing under one lock keeps
+    /// two writers that both see a full memtable from freezing twice.
+    pub fn freeze_if_full(&self) -> Option<FrozenMemtable<K, V, MAX_HEIGHT, SEED>> {
+        let inner = self.inner.write().unwrap();
+        if !self.is_full() {
+            return None;
+        }
+        Some(self.freeze_locked(inner))
+    }
+
+    fn freeze_locked(
+        &self,
+        mut inner: RwLockWriteGuard<'_, SkipListInner<K, Option<V>, MAX_HEIGHT, SEED>>,
+    ) -> FrozenMemtable<K, V, MAX_HEIGHT, SEED> {
+        let entries = mem::replace(&mut *inner, SkipListInner::new());
+        FrozenMemtable {
+            inner: RwLock::new(entries),
+            bytes: self.bytes.swap(0, Ordering::Relaxed),
+        }
+    }
+
+    /// Writes `value` under `key`, replacing its value or tombstone.
+    pub fn put(&self, key: K, value: V) {
+        self.write(key, Some(value));
+    }
+
+    /// Deletes `key` by writing a tombstone, whether the memtable holds the
+    /// key or not, since older tables may.
+    pub fn delete(&self, key: K) {
+        self.write(key, None);
+    }
+
+    fn write(&self, key: K, value: Option<V>) {
+        let key_bytes = key.as_ref().len();
+        let value_bytes = value.as_ref().map_or(0, |value| value.as_ref().len());
+        let mut inner = self.inner.write().unwrap();
+        let bytes = self.bytes.load(Ordering::Relaxed) + value_bytes;
+        let bytes = match inner.upsert(key, value) {
+            Some(old) => bytes - old.map_or(0, |old| old.as_ref().len()),
+            None => bytes + key_bytes,
+        };
+        self.bytes.store(bytes, Ordering::Relaxed);
+    }
+}
+
+impl<K: Ord + AsRef<[u8]>, V: AsRef<[u8]>, const MAX_HEIGHT: usize, const SEED: u32> Default
+    for Memtable<K, V, MAX_HEIGHT, SEED>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The read-only entries of a frozen [`Memtable`], waiting to be flushed.
+///
+/// Iterating yields every entry in key order, with `None` for tombstones,
+/// which is the order a sorted table on disk is written in. Consuming the
+/// table with `into_iter` moves the entries out instead of cloning them.
+pub struct FrozenMemtable<K, V, const MAX_HEIGHT: usize = 14, const SEED: u32 = 15445> {
+    /// Never written; the lock only lends out read guards for iterators.
+    inner: RwLock<SkipListInner<K, Option<V>, MAX_HEIGHT, SEED>>,
+    bytes: usize,
+}
+
+impl<K: Ord, V, const MAX_HEIGHT: usize, const SEED: u32> FrozenMemtable<K, V, MAX_HEIGHT, SEED> {
+    /// Returns the number of entries, tombstones included.
+    pub fn size(&self) -> usize {
+        self.inner.read().unwrap().size()
+    }
+
+    /// Returns the bytes the keys and values took when the table was frozen.
+    pub fn approximate_bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Looks up `key` like [`Memtable::get`].
+    pub fn get<Q>(&self, key: &Q) -> Option<Option<V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        V: Clone,
+    {
+        lookup(&self.inner.read().unwrap(), key)
+    }
+
+    /// Returns an iterator over the entries in key order, with `None` for
+    /// tombstones.
+    pub fn iter(&self) -> MemtableIter<'_, K, V, MAX_HEIGHT, SEED> {
+        MemtableIter {
+            cursor: Cursor::new(self.inner.read().unwrap()),
+        }
+    }
+}
+
+fn lookup<K, V: Clone, Q, const MAX_HEIGHT: usize, const SEED: u32>(
+    inner: &SkipListInner<K, Option<V>, MAX_HEIGHT, SEED>,
+    key: &Q,
+) -> Option<Option<V>>
+where
+    K: Ord + Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    let node = inner.find(key)?;
+    let node_read_lock = node.read().unwrap();
+    node_read_lock.value().cloned()
+}
+
+impl<K: Ord, V, const MAX_HEIGHT: usize, const SEED: u32> IntoIterator
+    for FrozenMemtable<K, V, MAX_HEIGHT, SEED>
+{
+    type Item = (K, Option<V>);
+    type IntoIter = IntoIter<(K, Option<V>)>;
+
+    /// Moves the entries out in key order.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self.inner.into_inner().unwrap().drain())
+    }
+}
+
+impl<'a, K: Ord + Clone, V: Clone, const MAX_HEIGHT: usize, const SEED: u32> IntoIterator
+    for &'a FrozenMemtable<K, V, MAX_HEIGHT, SEED>
+{
+    type Item = (K, Option<V>);
+    type IntoIter = MemtableIter<'a, K, V, MAX_HEIGHT, SEED>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`FrozenMemtable`] in key order.
+///
+/// Entries are cloned as they are yielded, `None` standing for a tombstone.
+pub struct MemtableIter<'a, K, V, const MAX_HEIGHT: usize = 14, const SEED: u32 = 15445> {
+    cursor: Cursor<'a, K, Option<V>, MAX_HEIGHT, SEED, OrdComparator>,
+}
+
+impl<K: Ord + Clone, V: Clone, const MAX_HEIGHT: usize, const SEED: u32> Iterator
+    for MemtableIter<'_, K, V, MAX_HEIGHT, SEED>
+{
+    type Item = (K, Option<V>);
+
+    fn next(&mut self) -> Option<(K, Option<V>)> {
+        self.cursor
+            .next_with(|key, value| (key.clone(), value.clone()))
+    }
+}
+
+impl<K: Ord + Clone, V: Clone, const MAX_HEIGHT: usize, const SEED: u32> FusedIterator
+    for MemtableIter<'_, K, V, MAX_HEIGHT, SEED>
+{
+}
+
+impl<K: Ord + Debug, V, const MAX_HEIGHT: usize, const SEED: u32> Display
+    for Memtable<K, V, MAX_HEIGHT, SEED>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.inner.read().unwrap(), f)
+    }
+}
+
+impl<K: Ord + Debug, V, const MAX_HEIGHT: usize, const SEED: u32> Debug
+    for Memtable<K, V, MAX_HEIGHT, SEED>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.inner.read().unwrap(), f)
+    }
+}
+
+#[cfg(test)]
+mod memtable_test;
@@ -0,0 +1,141 @@
+use std::sync::Arc;
+use std::sync::Barrier;
+use std::sync::Mutex;
+use std::thread::scope;
+
+use super::*;
+
+#[test]
+fn put_get_test() {
+    let memtable = Memtable::<String, String>::new();
+    assert!(memtable.is_empty());
+
+    memtable.put("b".to_string(), "two".to_string());
+    memtable.put("a".to_string(), "one".to_string());
+    assert_eq!(memtable.get("a"), Some(Some("one".to_string())));
+    assert_eq!(memtable.get("c"), None);
+
+    memtable.put("a".to_string(), "uno".to_string());
+    assert_eq!(memtable.get("a"), Some(Some("uno".to_string())));
+    assert_eq!(memtable.size(), 2);
+}
+
+#[test]
+fn tombstone_test() {
+    let memtable = Memtable::<String, String>::new();
+    memtable.put("a".to_string(), "one".to_string());
+    memtable.delete("a".to_string());
+    // Tombstones are kept even for keys the memtable never held
+    memtable.delete("b".to_string());
+
+    assert_eq!(memtable.get("a"), Some(None));
+    assert_eq!(memtable.get("b"), Some(None));
+    assert_eq!(memtable.size(), 2);
+
+    memtable.put("b".to_string(), "two".to_string());
+    assert_eq!(memtable.get("b"), Some(Some("two".to_string())));
+}
+
+#[test]
+fn approximate_bytes_test() {
+    let memtable = Memtable::<Vec<u8>, Vec<u8>>::new();
+    memtable.put(vec![0; 10], vec![0; 100]);
+    assert_eq!(memtable.approximate_bytes(), 110);
+    // Replacing a value only swaps the value bytes
+    memtable.put(vec![0; 10], vec![0; 40]);
+    assert_eq!(memtable.approximate_bytes(), 50);
+    memtable.delete(vec![0; 10]);
+    assert_eq!(memtable.approximate_bytes(), 10);
+    memtable.delete(vec![1; 5]);
+    assert_eq!(memtable.approximate_bytes(), 15);
+    memtable.put(vec![1; 5], vec![1; 5]);
+    assert_eq!(memtable.approximate_bytes(), 20);
+}
+
+#[test]
+fn freeze_test() {
+    let memtable = Memtable::<String, String>::new();
+    for key in ["c", "a", "b"] {
+        memtable.put(key.to_string(), key.repeat(2));
+    }
+    memtable.delete("d".to_string());
+    let bytes = memtable.approximate_bytes();
+
+    let frozen = memtable.freeze();
+    assert!(memtable.is_empty());
+    assert_eq!(memtable.approximate_bytes(), 0);
+    assert_eq!(frozen.approximate_bytes(), bytes);
+    assert_eq!(frozen.size(), 4);
+
+    // The fresh memtable takes writes without touching the frozen one
+    memtable.put("a".to_string(), "new".to_string());
+    assert_eq!(frozen.get("a"), Some(Some("aa".to_string())));
+    assert_eq!(frozen.get("d"), Some(None));
+    assert_eq!(memtable.get("b"), None);
+
+    let expected = vec![
+        ("a".to_string(), Some("aa".to_string())),
+        ("b".to_string(), Some("bb".to_string())),
+        ("c".to_string(), Some("cc".to_string())),
+        ("d".to_string(), None),
+    ];
+    assert_eq!(frozen.iter().collect::<Vec<_>>(), expected);
+    assert_eq!((&frozen).into_iter().count(), 4);
+    assert_eq!(frozen.into_iter().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn freeze_if_full_test() {
+    let memtable = Memtable::<Vec<u8>, Vec<u8>>::with_limit(100);
+    let mut frozen = Vec::new();
+    for key in 0..100u8 {
+        memtable.put(vec![key], vec![key; 9]);
+        frozen.extend(memtable.freeze_if_full());
+    }
+
+    assert_eq!(frozen.len(), 10);
+    assert!(memtable.is_empty());
+    for (i, table) in frozen.into_iter().enumerate() {
+        assert_eq!(table.approximate_bytes(), 100);
+        let keys: Vec<_> = table.into_iter().map(|(key, _)| key[0]).collect();
+        assert_eq!(keys, (i as u8 * 10..(i as u8 + 1) * 10).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn concurrent_freeze_test() {
+    const NUM_THREADS: u32 = 8;
+    const NUM_WRITES_PER_THREAD: u32 = 1_000;
+
+    let memtable = Memtable::<Vec<u8>, Vec<u8>>::with_limit(4096);
+    let frozen = Mutex::new(Vec::new());
+    let barrier = Arc::new(Barrier::new(NUM_THREADS as usize));
+
+    scope(|s| {
+        for i in 0..NUM_THREADS {
+            let barrier = Arc::clone(&barrier);
+            let memtable = &memtable;
+            let frozen = &frozen;
+            s.spawn(move || {
+                barrier.wait();
+                for n in 0..NUM_WRITES_PER_THREAD {
+                    let key = (i * NUM_WRITES_PER_THREAD + n).to_be_bytes().to_vec();
+                    memtable.put(key, vec![0; 12]);
+                    if let Some(table) = memtable.freeze_if_full() {
+                        frozen.lock().unwrap().push(table);
+                    }
+                }
+            });
+        }
+    });
+
+    // Every write ends up in exactly one table
+    let mut tables = frozen.into_inner().unwrap();
+    tables.push(memtable.freeze());
+    let mut keys: Vec<_> = tables.into_iter().flatten().map(|(key, _)| key).collect();
+    keys.sort();
+    let expected: Vec<_> = (0..NUM_THREADS * NUM_WRITES_PER_THREAD)
+        .map(|key| key.to_be_bytes().to_vec())
+        .collect();
+    assert_eq!(keys, expected);
+}
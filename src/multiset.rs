@@ -0,0 +1,203 @@
+use std::{
+    borrow::Borrow,
+    fmt::{Debug, Display},
+    ops::RangeBounds,
+    sync::{Arc, RwLock},
+};
+
+use crate::skiplist::{
+    checked_bounds, ConfigError, Iter, OrdComparator, Range, SkipListConfig, SkipListError,
+    SkipListInner, ValidationError,
+};
+
+/// A concurrent ordered multiset backed by a skiplist.
+///
+/// Unlike [`SkipList`](crate::SkipList), inserting a key that is already
+/// present always succeeds and adds another copy. Equal keys keep the order
+/// they were inserted in: a new copy is linked after all existing ones, and
+/// [`erase_one`](Self::erase_one) removes the oldest. Locking works as in
+/// [`SkipList`](crate::SkipList), with one lock over the whole set.
+///
+/// ```
+/// use p0::SkipMultiSet;
+///
+/// let set = SkipMultiSet::<i32>::new();
+/// for key in [3, 1, 3, 2, 3] {
+///     set.insert(key);
+/// }
+/// assert_eq!(set.count(&3), 3);
+/// assert_eq!(set.iter().collect::<Vec<_>>(), vec![1, 2, 3, 3, 3]);
+///
+/// assert!(set.erase_one(&3));
+/// assert_eq!(set.erase_all(&3), 2);
+/// assert_eq!(set.size(), 2);
+/// ```
+pub struct SkipMultiSet<K: Ord, const MAX_HEIGHT: usize = 14, const SEED: u32 = 15445> {
+    inner: Arc<RwLock<SkipListInner<K, (), MAX_HEIGHT, SEED>>>,
+}
+
+impl<K: Ord, const MAX_HEIGHT: usize, const SEED: u32> SkipMultiSet<K, MAX_HEIGHT, SEED> {
+    /// Creates an empty multiset.
+    pub fn new() -> Self {
+        SkipMultiSet {
+            inner: Arc::new(RwLock::new(SkipListInner::new())),
+        }
+    }
+
+    /// Creates an empty multiset whose tower heights follow `config`; see
+    /// [`SkipList::with_config`](crate::SkipList::with_config).
+    pub fn with_config(config: SkipListConfig) -> Result<Self, ConfigError> {
+        Ok(SkipMultiSet {
+            inner: Arc::new(RwLock::new(SkipListInner::with_config(config)?)),
+        })
+    }
+
+    /// Returns `true` if the multiset holds no keys.
+    pub fn empty(&self) -> bool {
+        self.inner.read().unwrap().empty()
+    }
+
+    /// Returns the number of keys in the multiset, counting every copy.
+    pub fn size(&self) -> usize {
+        self.inner.read().unwrap().size()
+    }
+
+    /// Inserts a copy of `key` after any equal keys already present.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the multiset is at its configured
+    /// [capacity](SkipListConfig::capacity).
+    pub fn insert(&self, key: K) {
+        self.try_insert(key)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Like [`insert`](Self::insert), but reports a poisoned or full
+    /// multiset instead of panicking.
+    pub fn try_insert(&self, key: K) -> Result<(), SkipListError> {
+        let mut inner = self.inner.write().map_err(|_| SkipListError::Poisoned)?;
+        inner.insert_last(key, ())
+    }
+
+    /// Returns the number of copies of `key`.
+    pub fn count<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.inner.read().unwrap().count(key)
+    }
+
+    /// Returns `true` if at least one copy of `key` is present.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.inner.read().unwrap().contains(key)
+    }
+
+    /// Removes the earliest inserted copy of `key`, returning `false` if
+    /// there was none.
+    pub fn erase_one<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut inner = self.inner.write().unwrap();
+        inner.remove(key).is_some()
+    }
+
+    /// Removes every copy of `key` and returns how many there were.
+    pub fn erase_all<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut inner = self.inner.write().unwrap();
+        let mut erased = 0;
+        while inner.remove(key).is_some() {
+            erased += 1;
+        }
+        erased
+    }
+
+    /// Removes every key from the multiset.
+    pub fn clear(&self) {
+        let mut inner = self.inner.write().unwrap();
+        inner.clear();
+    }
+
+    /// Returns an iterator over the keys in ascending order, with equal
+    /// keys in insertion order. See [`SkipList::iter`](crate::SkipList::iter)
+    /// for how it locks the multiset.
+    pub fn iter(&self) -> Iter<'_, K, MAX_HEIGHT, SEED> {
+        Iter::new(self.inner.read().unwrap())
+    }
+
+    /// Returns an iterator over the keys inside `range`, including every
+    /// copy of its ends.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if both
+    /// ends are equal and excluded.
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, MAX_HEIGHT, SEED>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = checked_bounds(&range, &OrdComparator, "SkipMultiSet");
+        Range::new(self.inner.read().unwrap(), start, end)
+    }
+
+    /// Checks the structural invariants like
+    /// [`SkipList::validate`](crate::SkipList::validate), except that equal
+    /// neighbours are allowed.
+    pub fn validate(&self) -> Result<(), ValidationError<K>>
+    where
+        K: Clone,
+    {
+        self.inner.read().unwrap().validate(true)
+    }
+}
+
+impl<'a, K: Ord + Clone, const MAX_HEIGHT: usize, const SEED: u32> IntoIterator
+    for &'a SkipMultiSet<K, MAX_HEIGHT, SEED>
+{
+    type Item = K;
+    type IntoIter = Iter<'a, K, MAX_HEIGHT, SEED>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Ord, const MAX_HEIGHT: usize, const SEED: u32> Default
+    for SkipMultiSet<K, MAX_HEIGHT, SEED>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Debug, const MAX_HEIGHT: usize, const SEED: u32> Display
+    for SkipMultiSet<K, MAX_HEIGHT, SEED>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.inner.read().unwrap(), f)
+    }
+}
+
+impl<K: Ord + Debug, const MAX_HEIGHT: usize, const SEED: u32> Debug
+    for SkipMultiSet<K, MAX_HEIGHT, SEED>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.inner.read().unwrap(), f)
+    }
+}
+
+#[cfg(test)]
+mod multiset_test;
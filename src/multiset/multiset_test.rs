@@ -0,0 +1,156 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+use std::sync::Barrier;
+use std::thread::scope;
+
+use super::*;
+
+/// Ordered by `key` only, so equal events can be told apart by `id`.
+#[derive(Debug, Clone, Copy)]
+struct Event {
+    key: i32,
+    id: usize,
+}
+
+impl PartialEq for Event {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for Event {}
+
+impl Ord for Event {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl PartialOrd for Event {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn event(key: i32) -> Event {
+    Event { key, id: 0 }
+}
+
+#[test]
+fn insert_count_test() {
+    let set = SkipMultiSet::<i32>::new();
+    assert!(set.empty());
+
+    for i in 0..100 {
+        set.insert(i % 10);
+    }
+    assert_eq!(set.size(), 100);
+    for i in 0..10 {
+        assert_eq!(set.count(&i), 10);
+        assert!(set.contains(&i));
+    }
+    assert_eq!(set.count(&10), 0);
+    assert!(!set.contains(&-1));
+
+    let keys: Vec<_> = set.iter().collect();
+    let mut expected: Vec<_> = (0..100).map(|i| i % 10).collect();
+    expected.sort();
+    assert_eq!(keys, expected);
+    assert_eq!(set.validate(), Ok(()));
+}
+
+#[test]
+fn erase_test() {
+    let set = SkipMultiSet::<i32>::new();
+    for key in [5, 1, 5, 3, 5, 1] {
+        set.insert(key);
+    }
+
+    assert!(set.erase_one(&5));
+    assert_eq!(set.count(&5), 2);
+    assert_eq!(set.erase_all(&5), 2);
+    assert!(!set.erase_one(&5));
+    assert_eq!(set.erase_all(&5), 0);
+    assert_eq!(set.erase_all(&1), 2);
+    assert_eq!(set.iter().collect::<Vec<_>>(), vec![3]);
+    assert_eq!(set.validate(), Ok(()));
+
+    set.clear();
+    assert!(set.empty());
+    assert_eq!(set.validate(), Ok(()));
+}
+
+#[test]
+fn insertion_order_test() {
+    let set = SkipMultiSet::<Event>::new();
+    // Interleave the keys so that equal events land on towers of all heights
+    for id in 0..300 {
+        set.insert(Event {
+            key: (id % 3) as i32,
+            id,
+        });
+    }
+    assert_eq!(set.validate(), Ok(()));
+
+    let ids =
+        |key: i32| -> Vec<usize> { set.range(event(key)..=event(key)).map(|e| e.id).collect() };
+    for key in 0..3 {
+        let expected: Vec<_> = (0..300).filter(|id| id % 3 == key as usize).collect();
+        assert_eq!(ids(key), expected);
+    }
+
+    // Erasing drops the oldest copy first
+    for _ in 0..10 {
+        assert!(set.erase_one(&event(1)));
+    }
+    assert_eq!(ids(1), (10..100).map(|i| i * 3 + 1).collect::<Vec<_>>());
+
+    let backwards: Vec<_> = set
+        .iter()
+        .rev()
+        .filter(|e| e.key == 2)
+        .map(|e| e.id)
+        .collect();
+    assert_eq!(
+        backwards,
+        (0..100).rev().map(|i| i * 3 + 2).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn range_test() {
+    let set = SkipMultiSet::<i32>::new();
+    for key in [4, 2, 2, 6, 4, 8, 4] {
+        set.insert(key);
+    }
+
+    assert_eq!(set.range(2..=4).collect::<Vec<_>>(), vec![2, 2, 4, 4, 4]);
+    assert_eq!(set.range(3..6).collect::<Vec<_>>(), vec![4, 4, 4]);
+    assert_eq!(set.range(5..).rev().collect::<Vec<_>>(), vec![8, 6]);
+    assert_eq!(set.range(9..).count(), 0);
+}
+
+#[test]
+fn concurrent_insert_test() {
+    let set = Arc::new(SkipMultiSet::<i32>::new());
+    let num_threads = 8;
+    let barrier = Arc::new(Barrier::new(num_threads));
+    scope(|s| {
+        for _ in 0..num_threads {
+            let set = set.clone();
+            let barrier = barrier.clone();
+            s.spawn(move || {
+                barrier.wait();
+                for key in 0..100 {
+                    set.insert(key);
+                }
+            });
+        }
+    });
+
+    assert_eq!(set.size(), num_threads * 100);
+    for key in 0..100 {
+        assert_eq!(set.count(&key), num_threads);
+    }
+    assert_eq!(set.validate(), Ok(()));
+}
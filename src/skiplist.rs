@@ -1,91 +1,893 @@
-use rand_core::RngCore;
 use std::{
     array,
     borrow::Borrow,
     cmp::Ordering,
     fmt::{Debug, Display},
+    hash::{Hash, Hasher},
+    mem,
+    ops::{Bound, RangeBounds},
     sync::{Arc, RwLock},
 };
 
 use mt19937::MT19937;
 
-pub struct SkipList<K: Ord, const MAX_HEIGHT: usize = 14, const SEED: u32 = 15445> {
-    inner: Arc<RwLock<SkipListInner<K, MAX_HEIGHT, SEED>>>,
+mod comparator;
+mod config;
+mod error;
+mod height;
+mod iter;
+mod snapshot;
+mod validate;
+
+pub use comparator::{Comparator, OrdComparator};
+pub use config::{ConfigError, SkipListConfig};
+pub use error::SkipListError;
+pub use height::{
+    HeightGenerator, Mt19937Heights, RngHeights, ScriptedHeights, ThreadLocalHeights,
+    ThreadLocalRng,
+};
+pub use iter::{IntoIter, Iter, Range};
+pub use snapshot::{Snapshot, SnapshotIter};
+pub use validate::ValidationError;
+
+pub(crate) use height::AtomicSplitMix;
+pub(crate) use iter::Cursor;
+use snapshot::Versions;
+
+/// A concurrent ordered set backed by a skiplist.
+///
+/// Every operation takes a lock on the whole list, so a `SkipList` can be
+/// shared between threads behind an `Arc` and used through `&self`.
+/// `MAX_HEIGHT` bounds the height of a tower and `SEED` seeds the MT19937
+/// generator that draws tower heights, which makes the layout reproducible.
+/// Both can be overridden at runtime, along with the probability that a tower
+/// grows, through [`SkipList::with_config`], and [`SkipList::with_generator`]
+/// swaps in any other [`HeightGenerator`] as `H`. Keys are ordered by `C`,
+/// their `Ord` implementation unless [`SkipList::with_comparator`] picks
+/// another [`Comparator`].
+///
+/// ```
+/// use p0::SkipList;
+///
+/// let list = SkipList::<i32>::new();
+/// assert!(list.insert(2));
+/// assert!(list.insert(1));
+/// assert!(!list.insert(2));
+///
+/// assert!(list.contains(&1));
+/// assert!(list.erase(&1));
+/// assert!(!list.contains(&1));
+/// assert_eq!(list.size(), 1);
+/// ```
+pub struct SkipList<
+    K,
+    const MAX_HEIGHT: usize = 14,
+    const SEED: u32 = 15445,
+    C = OrdComparator,
+    H = Mt19937Heights,
+> {
+    inner: Arc<RwLock<SkipListInner<K, (), MAX_HEIGHT, SEED, C, H>>>,
+}
+
+impl<K, const MAX_HEIGHT: usize, const SEED: u32> SkipList<K, MAX_HEIGHT, SEED> {
+    /// Creates an empty list that orders its keys with `cmp` instead of
+    /// `Ord`. See [`Comparator`] for an example.
+    ///
+    /// ```
+    /// use p0::SkipList;
+    ///
+    /// let list = SkipList::<i32>::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+    /// for key in [2, 3, 1] {
+    ///     list.insert(key);
+    /// }
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![3, 2, 1]);
+    /// ```
+    pub fn with_comparator<C: Comparator<K>>(cmp: C) -> SkipList<K, MAX_HEIGHT, SEED, C> {
+        Self::with_comparator_and_generator(cmp, Mt19937Heights::seeded(SEED))
+    }
+
+    /// Creates an empty list that orders its keys with `cmp` and draws
+    /// tower heights from `heights`; see [`with_comparator`](Self::with_comparator)
+    /// and [`with_generator`](SkipList::with_generator).
+    ///
+    /// ```
+    /// use p0::{ScriptedHeights, SkipList};
+    ///
+    /// let list = SkipList::<i32>::with_comparator_and_generator(
+    ///     |a: &i32, b: &i32| b.cmp(a),
+    ///     ScriptedHeights::new([1, 2]),
+    /// );
+    /// list.insert(1);
+    /// list.insert(2);
+    /// assert_eq!(format!("{list}"), "Height: 2 | Size: 2\n[H 2], [2 2], [1 1], NIL, ");
+    /// ```
+    pub fn with_comparator_and_generator<C: Comparator<K>, H: HeightGenerator>(
+        cmp: C,
+        heights: H,
+    ) -> SkipList<K, MAX_HEIGHT, SEED, C, H> {
+        SkipList {
+            inner: Arc::new(RwLock::new(SkipListInner::with_generator(
+                heights, MAX_HEIGHT, cmp,
+            ))),
+        }
+    }
+
+    /// Creates an empty list that orders its keys with `cmp` and whose
+    /// tower heights follow `config`; see [`with_comparator`](Self::with_comparator)
+    /// and [`with_config`](SkipList::with_config), which this fails like.
+    ///
+    /// ```
+    /// use p0::{SkipList, SkipListConfig};
+    ///
+    /// let config = SkipListConfig::new().capacity(2);
+    /// let list =
+    ///     SkipList::<i32>::with_comparator_and_config(|a: &i32, b: &i32| b.cmp(a), config).unwrap();
+    /// list.insert(1);
+    /// list.insert(2);
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![2, 1]);
+    /// assert!(list.try_insert(3).is_err());
+    /// ```
+    pub fn with_comparator_and_config<C: Comparator<K>>(
+        cmp: C,
+        config: SkipListConfig,
+    ) -> Result<SkipList<K, MAX_HEIGHT, SEED, C>, ConfigError> {
+        Ok(SkipList {
+            inner: Arc::new(RwLock::new(SkipListInner::with_config_and_comparator(
+                config, cmp,
+            )?)),
+        })
+    }
 }
 
-impl<K: Ord + Debug, const MAX_HEIGHT: usize, const SEED: u32> SkipList<K, MAX_HEIGHT, SEED> {
-    pub fn new() -> Self {
+impl<K, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>>
+    SkipList<K, MAX_HEIGHT, SEED, C>
+{
+    /// Creates an empty list.
+    pub fn new() -> Self
+    where
+        C: Default,
+    {
         SkipList {
             inner: Arc::new(RwLock::new(SkipListInner::new())),
         }
     }
 
+    /// Creates an empty list that draws tower heights from `heights`, up to
+    /// `MAX_HEIGHT`. The generator becomes part of the list's type, so
+    /// drawing a height is a direct call.
+    pub fn with_generator<H: HeightGenerator>(heights: H) -> SkipList<K, MAX_HEIGHT, SEED, C, H>
+    where
+        C: Default,
+    {
+        SkipList {
+            inner: Arc::new(RwLock::new(SkipListInner::with_generator(
+                heights,
+                MAX_HEIGHT,
+                C::default(),
+            ))),
+        }
+    }
+
+    /// Creates an empty list whose tower heights follow `config` instead
+    /// of the const-generic defaults.
+    ///
+    /// Fails if the promotion probability is not strictly between 0 and 1,
+    /// or if the maximum height is 0 or exceeds `MAX_HEIGHT`.
+    pub fn with_config(config: SkipListConfig) -> Result<Self, ConfigError>
+    where
+        C: Default,
+    {
+        Ok(SkipList {
+            inner: Arc::new(RwLock::new(SkipListInner::with_config(config)?)),
+        })
+    }
+
+    /// Builds a list from keys in ascending order in O(n), skipping
+    /// duplicates. See [`extend_sorted`](Self::extend_sorted).
+    ///
+    /// ```
+    /// use p0::{SkipList, SkipListError};
+    ///
+    /// let list = SkipList::<i32>::from_sorted_iter([1, 2, 2, 3]).unwrap();
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+    ///
+    /// let unsorted = SkipList::<i32>::from_sorted_iter([1, 3, 2]);
+    /// assert!(matches!(unsorted, Err(SkipListError::Unsorted { index: 2, .. })));
+    /// ```
+    pub fn from_sorted_iter(keys: impl IntoIterator<Item = K>) -> Result<Self, SkipListError>
+    where
+        C: Default,
+    {
+        let list = Self::new();
+        list.extend_sorted(keys)?;
+        Ok(list)
+    }
+}
+
+impl<K, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator>
+    SkipList<K, MAX_HEIGHT, SEED, C, H>
+{
+    /// Returns `true` if the list holds no keys.
     pub fn empty(&self) -> bool {
         self.inner.read().unwrap().empty()
     }
 
+    /// Returns the number of keys in the list.
     pub fn size(&self) -> usize {
         self.inner.read().unwrap().size()
     }
 
+    /// Inserts `key`, returning `false` if it was already present.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is new and the list is at its configured
+    /// [capacity](SkipListConfig::capacity).
     pub fn insert(&self, key: K) -> bool {
         let mut inner = self.inner.write().unwrap();
-        inner.insert(key)
+        inner.insert(key, ())
+    }
+
+    /// Inserts `key` with a tower of exactly `height` levels instead of
+    /// drawing one, returning `false` if it was already present.
+    ///
+    /// The height generator is left untouched. A list's layout only depends
+    /// on which height each key got, so inserting every key with the height
+    /// it has elsewhere rebuilds the same towers, in any order:
+    ///
+    /// ```
+    /// use p0::SkipList;
+    ///
+    /// let list = SkipList::<i32>::new();
+    /// list.start_recording();
+    /// for key in [5, 3, 8] {
+    ///     list.insert(key);
+    /// }
+    /// let heights = list.stop_recording().unwrap();
+    ///
+    /// let rebuilt = SkipList::<i32>::new();
+    /// for (key, height) in [5, 3, 8].into_iter().zip(heights).rev() {
+    ///     rebuilt.insert_with_height(key, height);
+    /// }
+    /// assert_eq!(format!("{rebuilt}"), format!("{list}"));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `height` is 0 or above the list's maximum height.
+    pub fn insert_with_height(&self, key: K, height: usize) -> bool {
+        let mut inner = self.inner.write().unwrap();
+        inner.insert_with_height(key, (), height)
+    }
+
+    /// Inserts `keys`, which must be in ascending order, in a single
+    /// left-to-right pass that costs O(n + m) for n new and m existing keys
+    /// rather than O(n log m). Keys already present are skipped, and the
+    /// number of inserted keys is returned.
+    ///
+    /// Stops with [`SkipListError::Unsorted`] at the first key smaller than
+    /// the one before it, and with [`SkipListError::CapacityExceeded`] once
+    /// the list is full. The keys before that stay inserted.
+    pub fn extend_sorted(&self, keys: impl IntoIterator<Item = K>) -> Result<usize, SkipListError> {
+        let mut inner = self.inner.write().map_err(|_| SkipListError::Poisoned)?;
+        inner.extend_sorted(keys.into_iter().map(|key| (key, ())))
+    }
+
+    /// Starts recording the height of every tower the list links from now
+    /// on, in order, discarding any earlier recording.
+    ///
+    /// Replaying the same inserts against a list built with
+    /// [`ScriptedHeights`] over the recording reproduces the layout; so
+    /// does [`insert_with_height`](Self::insert_with_height).
+    pub fn start_recording(&self) {
+        self.inner.write().unwrap().start_recording();
+    }
+
+    /// Returns the heights recorded so far, or `None` if the list is not
+    /// recording.
+    pub fn recorded_heights(&self) -> Option<Vec<usize>> {
+        self.inner
+            .read()
+            .unwrap()
+            .recorded_heights()
+            .map(<[usize]>::to_vec)
+    }
+
+    /// Stops recording and returns the recorded heights, or `None` if the
+    /// list was not recording.
+    pub fn stop_recording(&self) -> Option<Vec<usize>> {
+        self.inner.write().unwrap().stop_recording()
+    }
+
+    /// Like [`insert`](Self::insert), but reports a poisoned or full list
+    /// instead of panicking; see [`SkipListError`].
+    pub fn try_insert(&self, key: K) -> Result<bool, SkipListError> {
+        let mut inner = self.inner.write().map_err(|_| SkipListError::Poisoned)?;
+        inner.try_insert(key, ())
+    }
+
+    /// Like [`erase`](Self::erase), but reports a poisoned list instead of
+    /// panicking.
+    pub fn try_erase<Q>(&self, key: &Q) -> Result<bool, SkipListError>
+    where
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
+        let mut inner = self.inner.write().map_err(|_| SkipListError::Poisoned)?;
+        Ok(inner.remove(key).is_some())
+    }
+
+    /// Like [`contains`](Self::contains), but reports a poisoned list
+    /// instead of panicking.
+    pub fn try_contains<Q>(&self, key: &Q) -> Result<bool, SkipListError>
+    where
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
+        let inner = self.inner.read().map_err(|_| SkipListError::Poisoned)?;
+        Ok(inner.contains(key))
+    }
+
+    /// Returns `true` if a thread panicked while writing to the list.
+    pub fn is_poisoned(&self) -> bool {
+        self.inner.is_poisoned()
+    }
+
+    /// Marks the list as no longer poisoned, without checking it. Prefer
+    /// [`recover`](Self::recover).
+    pub fn clear_poison(&self) {
+        self.inner.clear_poison();
+    }
+
+    /// Clears the poison left by a panicking writer and revalidates the
+    /// list, returning the first broken invariant if there is one. The list
+    /// is usable again either way; after a violation, [`clear`](Self::clear)
+    /// it before relying on its contents. See [`SkipListError`] for an
+    /// example.
+    pub fn recover(&self) -> Result<(), ValidationError<K>>
+    where
+        K: Clone,
+    {
+        self.clear_poison();
+        self.validate()
     }
 
-    pub fn erase<Q>(&self, key: Q) -> bool
+    /// Removes `key`, returning `false` if it was not present.
+    pub fn erase<Q>(&self, key: &Q) -> bool
     where
-        Q: Borrow<K>,
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
     {
         let mut inner = self.inner.write().unwrap();
-        inner.erase(key)
+        inner.remove(key).is_some()
     }
 
-    pub fn contains<Key>(&self, key: Key) -> bool
+    /// Returns `true` if `key` is in the list.
+    pub fn contains<Q>(&self, key: &Q) -> bool
     where
-        Key: Borrow<K>,
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
     {
         self.inner.read().unwrap().contains(key)
     }
 
+    /// Removes every key from the list.
     pub fn clear(&self) {
         let mut inner = self.inner.write().unwrap();
         inner.clear();
     }
+
+    /// Checks the structural invariants of the list and returns the first
+    /// violation found:
+    ///
+    /// - keys strictly increase along every level, and every node reached
+    ///   on a level has a tower that tall;
+    /// - every level holds exactly the nodes of the level below whose towers
+    ///   reach it, so it is a subsequence of that level and no `Nil` ends it
+    ///   early;
+    /// - `size` matches the number of nodes on level 0;
+    /// - the list's height matches its tallest tower.
+    ///
+    /// Takes a read lock for the whole check, which walks every level and
+    /// costs O(n · `MAX_HEIGHT`).
+    ///
+    /// ```
+    /// use p0::SkipList;
+    ///
+    /// let list = SkipList::<i32>::new();
+    /// for key in 0..100 {
+    ///     list.insert(key);
+    /// }
+    /// assert_eq!(list.validate(), Ok(()));
+    /// ```
+    pub fn validate(&self) -> Result<(), ValidationError<K>>
+    where
+        K: Clone,
+    {
+        self.inner.read().unwrap().validate(false)
+    }
+
+    /// Returns an estimate of the bytes allocated for the list's nodes,
+    /// for comparison with [`ArenaSkipList::allocated_bytes`].
+    ///
+    /// [`ArenaSkipList::allocated_bytes`]: crate::ArenaSkipList::allocated_bytes
+    pub fn allocated_bytes(&self) -> usize {
+        mem::size_of::<Self>() + self.inner.read().unwrap().allocated_bytes()
+    }
+
+    /// Returns an iterator over the keys in ascending order.
+    ///
+    /// The iterator keeps the list read-locked until it is dropped; see
+    /// [`Iter`] for what that means for concurrent writers.
+    ///
+    /// ```
+    /// use p0::SkipList;
+    ///
+    /// let list = SkipList::<i32>::new();
+    /// for key in [3, 1, 2] {
+    ///     list.insert(key);
+    /// }
+    /// assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+    /// assert_eq!(list.iter().rev().collect::<Vec<_>>(), vec![3, 2, 1]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, MAX_HEIGHT, SEED, C, H> {
+        Iter::new(self.inner.read().unwrap())
+    }
+
+    /// Takes a point-in-time view of the list that later writes do not
+    /// change. Unlike [`iter`](Self::iter), scanning a [`Snapshot`] does not
+    /// keep writers waiting until the scan ends.
+    pub fn snapshot(&self) -> Snapshot<K, MAX_HEIGHT, SEED, C, H>
+    where
+        K: Clone,
+    {
+        Snapshot::new(self.inner.clone())
+    }
+
+    /// Returns an iterator over the keys inside `range`, in ascending order.
+    ///
+    /// The start of the range is found with a top-down search, after which
+    /// keys are streamed along level 0 until the end of the range. Like
+    /// [`iter`](Self::iter), the returned [`Range`] keeps the list
+    /// read-locked until it is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if both
+    /// ends are equal and excluded, just like `BTreeSet::range`.
+    ///
+    /// ```
+    /// use std::ops::Bound::{Excluded, Included};
+    ///
+    /// use p0::SkipList;
+    ///
+    /// let list = SkipList::<i32>::new();
+    /// for key in 0..10 {
+    ///     list.insert(key);
+    /// }
+    /// assert_eq!(list.range(3..6).collect::<Vec<_>>(), vec![3, 4, 5]);
+    /// assert_eq!(list.range(8..).collect::<Vec<_>>(), vec![8, 9]);
+    /// assert_eq!(list.range((Excluded(1), Included(3))).collect::<Vec<_>>(), vec![2, 3]);
+    /// ```
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, MAX_HEIGHT, SEED, C, H>
+    where
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let inner = self.inner.read().unwrap();
+        let (start, end) = checked_bounds(&range, &inner.cmp, "SkipList");
+        Range::new(inner, start, end)
+    }
+
+    /// Returns the smallest key that is not less than `key`.
+    ///
+    /// This is the same key as [`ceiling`](Self::ceiling); the name follows
+    /// C++'s `std::lower_bound`.
+    pub fn lower_bound<Q>(&self, key: &Q) -> Option<K>
+    where
+        K: Borrow<Q> + Clone,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
+        self.key_at(|inner| inner.lower_node(Bound::Included(key)))
+    }
+
+    /// Returns the smallest key that is greater than `key`.
+    pub fn upper_bound<Q>(&self, key: &Q) -> Option<K>
+    where
+        K: Borrow<Q> + Clone,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
+        self.key_at(|inner| inner.lower_node(Bound::Excluded(key)))
+    }
+
+    /// Returns the greatest key that is less than or equal to `key`.
+    ///
+    /// ```
+    /// use p0::SkipList;
+    ///
+    /// let list = SkipList::<u64>::new();
+    /// for t in [10, 20, 30] {
+    ///     list.insert(t);
+    /// }
+    /// assert_eq!(list.floor(&25), Some(20));
+    /// assert_eq!(list.floor(&30), Some(30));
+    /// assert_eq!(list.floor(&5), None);
+    /// assert_eq!(list.ceiling(&25), Some(30));
+    /// ```
+    pub fn floor<Q>(&self, key: &Q) -> Option<K>
+    where
+        K: Borrow<Q> + Clone,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
+        self.key_at(|inner| inner.upper_node(Bound::Included(key)))
+    }
+
+    /// Returns the smallest key that is greater than or equal to `key`.
+    pub fn ceiling<Q>(&self, key: &Q) -> Option<K>
+    where
+        K: Borrow<Q> + Clone,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
+        self.key_at(|inner| inner.lower_node(Bound::Included(key)))
+    }
+
+    /// Returns the smallest key in the list.
+    pub fn first(&self) -> Option<K>
+    where
+        K: Clone,
+    {
+        self.key_at(|inner| inner.first_node())
+    }
+
+    /// Returns the greatest key in the list.
+    ///
+    /// The last node is reached with a top-down search, so this is
+    /// O(log n) rather than a walk along level 0.
+    pub fn last(&self) -> Option<K>
+    where
+        K: Clone,
+    {
+        self.key_at(|inner| inner.last_node())
+    }
+
+    /// Removes and returns the smallest key in the list.
+    ///
+    /// ```
+    /// use p0::SkipList;
+    ///
+    /// let list = SkipList::<i32>::new();
+    /// for key in [2, 3, 1] {
+    ///     list.insert(key);
+    /// }
+    /// assert_eq!(list.pop_first(), Some(1));
+    /// assert_eq!(list.pop_last(), Some(3));
+    /// assert_eq!(list.first(), Some(2));
+    /// assert_eq!(list.last(), Some(2));
+    /// assert_eq!(list.size(), 1);
+    /// ```
+    pub fn pop_first(&self) -> Option<K> {
+        let mut inner = self.inner.write().unwrap();
+        inner.pop_first().map(|(key, _)| key)
+    }
+
+    /// Removes and returns the greatest key in the list.
+    pub fn pop_last(&self) -> Option<K> {
+        let mut inner = self.inner.write().unwrap();
+        inner.pop_last().map(|(key, _)| key)
+    }
+
+    /// Returns the number of keys less than `key`, which is the index `key`
+    /// has, or would have, in iteration order. Runs in O(log n) by summing
+    /// the widths of the links skipped on the way down.
+    ///
+    /// ```
+    /// use p0::SkipList;
+    ///
+    /// let list = SkipList::<i32>::new();
+    /// for key in (0..100).map(|i| i * 10) {
+    ///     list.insert(key);
+    /// }
+    /// assert_eq!(list.rank(&250), 25);
+    /// assert_eq!(list.rank(&255), 26);
+    /// // The 90th percentile
+    /// assert_eq!(list.select(list.size() * 9 / 10), Some(900));
+    /// assert_eq!(list.count_range(100..200), 10);
+    /// ```
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
+        self.inner.read().unwrap().rank(key)
+    }
+
+    /// Returns the key at `index` in ascending order, or `None` if the list
+    /// holds no more than `index` keys. Runs in O(log n).
+    pub fn select(&self, index: usize) -> Option<K>
+    where
+        K: Clone,
+    {
+        self.key_at(|inner| inner.select(index))
+    }
+
+    /// Same as [`select`](Self::select).
+    pub fn nth(&self, index: usize) -> Option<K>
+    where
+        K: Clone,
+    {
+        self.select(index)
+    }
+
+    /// Returns the number of keys inside `range` in O(log n), without
+    /// visiting them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if both
+    /// ends are equal and excluded.
+    pub fn count_range<Q, R>(&self, range: R) -> usize
+    where
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let inner = self.inner.read().unwrap();
+        let (start, end) = checked_bounds(&range, &inner.cmp, "SkipList");
+        inner.count_range(start, end)
+    }
+
+    fn key_at(
+        &self,
+        search: impl FnOnce(&SkipListInner<K, (), MAX_HEIGHT, SEED, C, H>) -> Option<Link<K>>,
+    ) -> Option<K>
+    where
+        K: Clone,
+    {
+        let inner = self.inner.read().unwrap();
+        let node = search(&inner)?;
+        let node_read_lock = node.read().unwrap();
+        node_read_lock.key().cloned()
+    }
+}
+
+impl<
+        'a,
+        K: Clone,
+        const MAX_HEIGHT: usize,
+        const SEED: u32,
+        C: Comparator<K>,
+        H: HeightGenerator,
+    > IntoIterator for &'a SkipList<K, MAX_HEIGHT, SEED, C, H>
+{
+    type Item = K;
+    type IntoIter = Iter<'a, K, MAX_HEIGHT, SEED, C, H>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator> IntoIterator
+    for SkipList<K, MAX_HEIGHT, SEED, C, H>
+{
+    type Item = K;
+    type IntoIter = IntoIter<K>;
+
+    /// Moves the keys out in ascending order.
+    fn into_iter(self) -> IntoIter<K> {
+        let mut inner = self.inner.write().unwrap();
+        IntoIter::new(inner.drain().into_iter().map(|(key, _)| key).collect())
+    }
+}
+
+impl<K, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K> + Default> Default
+    for SkipList<K, MAX_HEIGHT, SEED, C>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K> + Default> FromIterator<K>
+    for SkipList<K, MAX_HEIGHT, SEED, C>
+{
+    /// Inserts the keys one by one. Prefer
+    /// [`from_sorted_iter`](SkipList::from_sorted_iter) for sorted keys.
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<K, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator> Extend<K>
+    for SkipList<K, MAX_HEIGHT, SEED, C, H>
+{
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        let mut inner = self.inner.write().unwrap();
+        for key in iter {
+            inner.insert(key, ());
+        }
+    }
 }
 
-impl<K: Ord + Debug, const MAX_HEIGHT: usize, const SEED: u32> Display
-    for SkipList<K, MAX_HEIGHT, SEED>
+impl<
+        'a,
+        K: Copy + 'a,
+        const MAX_HEIGHT: usize,
+        const SEED: u32,
+        C: Comparator<K>,
+        H: HeightGenerator,
+    > Extend<&'a K> for SkipList<K, MAX_HEIGHT, SEED, C, H>
+{
+    fn extend<I: IntoIterator<Item = &'a K>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+/// A deep copy: every tower keeps its height, so the copy prints the same.
+/// The copy draws later heights from a copy of the list's generator.
+impl<K, const MAX_HEIGHT: usize, const SEED: u32, C, H> Clone
+    for SkipList<K, MAX_HEIGHT, SEED, C, H>
+where
+    K: Clone,
+    C: Comparator<K> + Clone,
+    H: HeightGenerator + Clone,
+{
+    fn clone(&self) -> Self {
+        SkipList {
+            inner: Arc::new(RwLock::new(self.inner.read().unwrap().clone())),
+        }
+    }
+}
+
+/// Lists are equal if they hold equal keys in the same order, regardless of
+/// their tower heights.
+impl<
+        K: PartialEq,
+        const MAX_HEIGHT: usize,
+        const SEED: u32,
+        C: Comparator<K>,
+        H: HeightGenerator,
+    > PartialEq for SkipList<K, MAX_HEIGHT, SEED, C, H>
+{
+    fn eq(&self, other: &Self) -> bool {
+        if Arc::ptr_eq(&self.inner, &other.inner) {
+            return true;
+        }
+        // Both lists are locked in address order, so that `a == b` and
+        // `b == a` on two threads cannot each hold one lock while a queued
+        // writer keeps the other from them
+        let (ours, theirs) = if Arc::as_ptr(&self.inner) < Arc::as_ptr(&other.inner) {
+            let ours = self.inner.read().unwrap();
+            (ours, other.inner.read().unwrap())
+        } else {
+            let theirs = other.inner.read().unwrap();
+            (self.inner.read().unwrap(), theirs)
+        };
+        if ours.size() != theirs.size() {
+            return false;
+        }
+        let mut ours = Cursor::new(ours);
+        let mut theirs = Cursor::new(theirs);
+        while let Some(equal) = ours.next_with(|key, _| theirs.next_with(|other, _| key == other)) {
+            if equal != Some(true) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl<K: Eq, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator> Eq
+    for SkipList<K, MAX_HEIGHT, SEED, C, H>
+{
+}
+
+/// Hashes the length and then every key in order, like `BTreeSet`.
+impl<K: Hash, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator> Hash
+    for SkipList<K, MAX_HEIGHT, SEED, C, H>
+{
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        let inner = self.inner.read().unwrap();
+        inner.size().hash(state);
+        let mut cursor = Cursor::new(inner);
+        while cursor.next_with(|key, _| key.hash(state)).is_some() {}
+    }
+}
+
+impl<K: Debug, const MAX_HEIGHT: usize, const SEED: u32, C, H> Display
+    for SkipList<K, MAX_HEIGHT, SEED, C, H>
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         Display::fmt(&self.inner.read().unwrap(), f)
     }
 }
 
-impl<K: Ord + Debug, const MAX_HEIGHT: usize, const SEED: u32> Debug
-    for SkipList<K, MAX_HEIGHT, SEED>
+impl<K: Debug, const MAX_HEIGHT: usize, const SEED: u32, C, H> Debug
+    for SkipList<K, MAX_HEIGHT, SEED, C, H>
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         Debug::fmt(&self.inner.read().unwrap(), f)
     }
 }
 
-struct SkipListInner<K: Ord, const MAX_HEIGHT: usize = 14, const SEED: u32 = 15445> {
-    header: Arc<RwLock<Node<K>>>,
+pub(crate) struct SkipListInner<
+    K,
+    V,
+    const MAX_HEIGHT: usize = 14,
+    const SEED: u32 = 15445,
+    C = OrdComparator,
+    H = Mt19937Heights,
+> {
+    header: Link<K, V>,
     height: usize,
     size: usize,
-    rng: Arc<RwLock<MT19937>>,
+    max_height: usize,
+    heights: H,
+    /// Heights of the towers linked since recording started, if it did.
+    recorded: Option<Vec<usize>>,
+    capacity: Option<usize>,
+    cmp: C,
+    versions: Versions<K>,
+}
+
+impl<K, V, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>>
+    SkipListInner<K, V, MAX_HEIGHT, SEED, C>
+{
+    pub fn new() -> Self
+    where
+        C: Default,
+    {
+        Self::with_generator(Mt19937Heights::seeded(SEED), MAX_HEIGHT, C::default())
+    }
+
+    pub fn with_config(config: SkipListConfig) -> Result<Self, ConfigError>
+    where
+        C: Default,
+    {
+        Self::with_config_and_comparator(config, C::default())
+    }
+
+    pub fn with_config_and_comparator(config: SkipListConfig, cmp: C) -> Result<Self, ConfigError> {
+        let resolved = config.resolve::<MAX_HEIGHT, SEED>()?;
+        let rng = MT19937::new_with_slice_seed(&[resolved.seed]);
+        let mut inner = Self::with_generator(
+            RngHeights::with_promotion(rng, resolved.promotion),
+            resolved.max_height,
+            cmp,
+        );
+        inner.capacity = resolved.capacity;
+        Ok(inner)
+    }
 }
 
-impl<K: Ord + Debug, const MAX_HEIGHT: usize, const SEED: u32> SkipListInner<K, MAX_HEIGHT, SEED> {
-    pub fn new() -> Self {
-        let header = Arc::new(RwLock::new(Node::new_header(MAX_HEIGHT)));
-        let rng = MT19937::new_with_slice_seed(&[SEED]);
+impl<K, V, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator>
+    SkipListInner<K, V, MAX_HEIGHT, SEED, C, H>
+{
+    pub fn with_generator(heights: H, max_height: usize, cmp: C) -> Self {
         SkipListInner {
-            header,
+            header: Arc::new(RwLock::new(Node::new_header(MAX_HEIGHT))),
             height: 1,
             size: 0,
-            rng: Arc::new(RwLock::new(rng)),
+            max_height,
+            heights,
+            recorded: None,
+            capacity: None,
+            cmp,
+            versions: Versions::new(),
         }
     }
 
@@ -97,64 +899,259 @@ impl<K: Ord + Debug, const MAX_HEIGHT: usize, const SEED: u32> SkipListInner<K,
         self.size
     }
 
-    pub fn insert(&mut self, key: K) -> bool {
-        let (update, _cur, found) = self.trace(&key);
+    pub fn insert(&mut self, key: K, value: V) -> bool {
+        self.try_insert(key, value)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<bool, SkipListError> {
+        self.insert_at(key, value, None)
+    }
+
+    pub fn insert_with_height(&mut self, key: K, value: V, height: usize) -> bool {
+        assert!(
+            (1..=self.max_height).contains(&height),
+            "Tower height {height} is not between 1 and {}",
+            self.max_height
+        );
+        self.insert_at(key, value, Some(height))
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Inserts a tower of `height`, or of a freshly drawn height if `None`.
+    /// Heights are only drawn for keys that are not present yet.
+    fn insert_at(
+        &mut self,
+        key: K,
+        value: V,
+        height: Option<usize>,
+    ) -> Result<bool, SkipListError> {
+        let (update, ranks, _cur, found) = self.trace(&key);
 
         if found {
-            return false;
+            return Ok(false);
+        }
+        self.check_capacity()?;
+
+        self.link(update, ranks, key, value, height);
+        Ok(true)
+    }
+
+    /// Links a new node after every node whose key equals `key`, so equal
+    /// keys stay in insertion order.
+    pub fn insert_last(&mut self, key: K, value: V) -> Result<(), SkipListError> {
+        self.check_capacity()?;
+        let (update, ranks) = self.trace_after(&key);
+        self.link(update, ranks, key, value, None);
+        Ok(())
+    }
+
+    /// Inserts `entries`, which must be sorted, in one pass. Instead of
+    /// searching from the header for every key, the predecessors of the last
+    /// key are moved forward, and each new tower draws its height as usual.
+    /// Keys that are already present, in the list or earlier in `entries`,
+    /// are skipped. Returns how many keys were inserted.
+    pub fn extend_sorted(
+        &mut self,
+        entries: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<usize, SkipListError> {
+        self.extend_towers(entries.into_iter().map(|(key, value)| (key, value, None)))
+    }
+
+    /// Like [`extend_sorted`](Self::extend_sorted), but each entry may come
+    /// with the height of its tower.
+    fn extend_towers(
+        &mut self,
+        towers: impl IntoIterator<Item = (K, V, Option<usize>)>,
+    ) -> Result<usize, SkipListError> {
+        let mut update: [Link<K, V>; MAX_HEIGHT] = array::from_fn(|_| self.header.clone());
+        let mut ranks = [0; MAX_HEIGHT];
+        let mut last: Option<Link<K, V>> = None;
+        let mut inserted = 0;
+        for (index, (key, value, height)) in towers.into_iter().enumerate() {
+            if let Some(last) = &last {
+                match self.compare_key(&last.read().unwrap(), &key) {
+                    Some(Ordering::Greater) => return Err(SkipListError::Unsorted { index }),
+                    Some(Ordering::Equal) => continue,
+                    _ => {}
+                }
+            }
+            if let Some(node) = self.advance(&mut update, &mut ranks, &key) {
+                last = Some(node);
+                continue;
+            }
+            self.check_capacity()?;
+            self.link(update.clone(), ranks, key, value, height);
+            last = update[MAX_HEIGHT - 1].read().unwrap().next(0);
+            inserted += 1;
+        }
+        Ok(inserted)
+    }
+
+    /// Moves `update`, the predecessors of some key on every level, and
+    /// their positions `ranks` forward to the predecessors of the greater
+    /// `key`. Returns the node holding `key` if there is one.
+    fn advance(
+        &self,
+        update: &mut [Link<K, V>; MAX_HEIGHT],
+        ranks: &mut [usize; MAX_HEIGHT],
+        key: &K,
+    ) -> Option<Link<K, V>> {
+        let mut cur = self.header.clone();
+        let mut rank = 0;
+        let mut found = None;
+        for i in 0..MAX_HEIGHT {
+            let level = MAX_HEIGHT - i - 1;
+            // Resume from whichever is further along, this level's old
+            // predecessor or where the level above stopped
+            if ranks[i] > rank {
+                cur = update[i].clone();
+                rank = ranks[i];
+            }
+            loop {
+                let (next, width) = {
+                    let cur_read_lock = cur.read().unwrap();
+                    match cur_read_lock.next(level) {
+                        Some(arc) => (arc, cur_read_lock.width(level)),
+                        None => break,
+                    }
+                };
+                let next_key_cmp = self.compare_key(&next.read().unwrap(), key);
+                match next_key_cmp {
+                    Some(Ordering::Less) => {
+                        cur = next;
+                        rank += width;
+                    }
+                    Some(Ordering::Equal) => {
+                        found = Some(next);
+                        break;
+                    }
+                    _ => break,
+                }
+            }
+            update[i] = cur.clone();
+            ranks[i] = rank;
+        }
+        found
+    }
+
+    fn check_capacity(&self) -> Result<(), SkipListError> {
+        match self.capacity {
+            Some(capacity) if self.size >= capacity => {
+                Err(SkipListError::CapacityExceeded { capacity })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn upsert(&mut self, key: K, value: V) -> Option<V> {
+        self.try_upsert(key, value)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    pub fn try_upsert(&mut self, key: K, value: V) -> Result<Option<V>, SkipListError> {
+        let (update, ranks, cur, found) = self.trace(&key);
+
+        if found {
+            let node = cur
+                .read()
+                .map(|node| node.next(0))
+                .unwrap()
+                .expect("Node to update should be found here");
+            let mut node_write_lock = node.write().unwrap();
+            return Ok(node_write_lock.replace_value(value));
         }
+        self.check_capacity()?;
 
-        let new_height = self.random_height();
+        self.link(update, ranks, key, value, None);
+        Ok(None)
+    }
+
+    /// Links a new node right after `update`, whose positions are `ranks`.
+    fn link(
+        &mut self,
+        update: [Link<K, V>; MAX_HEIGHT],
+        ranks: [usize; MAX_HEIGHT],
+        key: K,
+        value: V,
+        height: Option<usize>,
+    ) {
+        let new_height = match height {
+            Some(height) => height,
+            None => self.random_height(),
+        };
+        if let Some(recorded) = &mut self.recorded {
+            recorded.push(new_height);
+        }
         if new_height > self.height {
             self.height = new_height;
         }
-        let new_node = Arc::new(RwLock::new(Node::new(key, new_height)));
-        for i in 0..new_height {
+        self.versions.prune();
+        let born = self.versions.current();
+        let new_node = Arc::new(RwLock::new(Node::new(key, value, new_height, born)));
+        let new_rank = ranks[MAX_HEIGHT - 1] + 1;
+        for i in 0..MAX_HEIGHT {
             let node_to_update = update[MAX_HEIGHT - i - 1].clone();
+            let rank = ranks[MAX_HEIGHT - i - 1];
+            let width = node_to_update.read().unwrap().width(i);
+            if i >= new_height {
+                // The link now spans the new node as well
+                node_to_update.write().unwrap().set_width(i, width + 1);
+                continue;
+            }
             {
                 let node_to_update_read_lock = node_to_update.read().unwrap();
                 if let Some(arc) = node_to_update_read_lock.next(i) {
                     let mut new_node_write_lock = new_node.write().unwrap();
-                    new_node_write_lock.set_next(i, arc);
+                    new_node_write_lock.set_next(i, arc, rank + width + 1 - new_rank);
                 }
             }
             {
                 let mut node_to_update_write_lock = node_to_update.write().unwrap();
-                node_to_update_write_lock.set_next(i, new_node.clone());
+                node_to_update_write_lock.set_next(i, new_node.clone(), new_rank - rank);
             }
         }
         self.size += 1;
-
-        true
     }
 
+    /// Returns the last node before `key` on every level, their positions
+    /// counting the header as 0, the bottom one again, and whether `key` is
+    /// present.
+    #[allow(clippy::type_complexity)]
     fn trace<Q>(
         &self,
-        key: Q,
+        key: &Q,
     ) -> (
-        [Arc<RwLock<Node<K>>>; MAX_HEIGHT],
-        Arc<RwLock<Node<K>>>,
+        [Link<K, V>; MAX_HEIGHT],
+        [usize; MAX_HEIGHT],
+        Link<K, V>,
         bool,
     )
     where
-        Q: Borrow<K>,
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
     {
         let mut cur = self.header.clone();
+        let mut rank = 0;
+        let mut ranks = [0; MAX_HEIGHT];
         let mut found = false;
-        let key = key.borrow();
-        let update: [Arc<RwLock<Node<K>>>; MAX_HEIGHT] = array::from_fn(|i| {
+        let update: [Link<K, V>; MAX_HEIGHT] = array::from_fn(|i| {
             let level = MAX_HEIGHT - i - 1;
             loop {
-                let next = {
+                let (next, width) = {
                     let cur_read_lock = cur.read().unwrap();
                     match cur_read_lock.next(level) {
-                        Some(arc) => arc,
+                        Some(arc) => (arc, cur_read_lock.width(level)),
                         None => break,
                     }
                 };
-                let next_key_cmp = next.read().map(|node| node.compare_key(key)).unwrap();
+                let next_key_cmp = self.compare_key(&next.read().unwrap(), key);
                 match next_key_cmp {
-                    Some(Ordering::Less) => cur = next.clone(),
+                    Some(Ordering::Less) => {
+                        cur = next.clone();
+                        rank += width;
+                    }
                     Some(Ordering::Equal) => {
                         // Key already exists
                         found = true;
@@ -163,19 +1160,53 @@ impl<K: Ord + Debug, const MAX_HEIGHT: usize, const SEED: u32> SkipListInner<K,
                     _ => break,
                 }
             }
+            ranks[i] = rank;
             cur.clone()
         });
-        return (update, cur, found);
+        (update, ranks, cur, found)
     }
 
-    pub fn erase<Q>(&mut self, key: Q) -> bool
+    /// Like [`trace`](Self::trace), but returns the last nodes not greater
+    /// than `key`, i.e. the predecessors of a node linked after any equal
+    /// keys, and their positions.
+    fn trace_after(&self, key: &K) -> ([Link<K, V>; MAX_HEIGHT], [usize; MAX_HEIGHT]) {
+        let mut cur = self.header.clone();
+        let mut rank = 0;
+        let mut ranks = [0; MAX_HEIGHT];
+        let update = array::from_fn(|i| {
+            let level = MAX_HEIGHT - i - 1;
+            loop {
+                let (next, width) = {
+                    let cur_read_lock = cur.read().unwrap();
+                    match cur_read_lock.next_inner(level) {
+                        Some(arc) => (arc, cur_read_lock.width(level)),
+                        None => break,
+                    }
+                };
+                if self.compare_key(&next.read().unwrap(), key) == Some(Ordering::Greater) {
+                    break;
+                }
+                cur = next;
+                rank += width;
+            }
+            ranks[i] = rank;
+            cur.clone()
+        });
+        (update, ranks)
+    }
+
+    /// Removes the first node whose key equals `key`, which is the earliest
+    /// inserted one among duplicates.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<(K, V)>
     where
-        Q: Borrow<K>,
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
     {
-        let (update, cur, found) = self.trace(key);
+        let (update, _ranks, cur, found) = self.trace(key);
 
         if !found {
-            return false;
+            return None;
         }
 
         let node_to_delete = cur
@@ -184,45 +1215,120 @@ impl<K: Ord + Debug, const MAX_HEIGHT: usize, const SEED: u32> SkipListInner<K,
             .unwrap()
             .expect("Node to erase should be found here");
 
+        Some(self.unlink(update, node_to_delete))
+    }
+
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        let node = self.first_node()?;
+        let update = self.trace_node(&node);
+        Some(self.unlink(update, node))
+    }
+
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let node = self.last_node()?;
+        let update = self.trace_node(&node);
+        Some(self.unlink(update, node))
+    }
+
+    /// Visits every entry in order, letting `f` update its value, and
+    /// removes the entries for which `f` returns `false`.
+    pub fn retain(&mut self, mut f: impl FnMut(&K, &mut V) -> bool) {
+        let mut cur = self.first_node();
+        while let Some(node) = cur {
+            let keep = {
+                let mut node_write_lock = node.write().unwrap();
+                node_write_lock
+                    .entry_mut()
+                    .is_none_or(|(key, value)| f(key, value))
+            };
+            cur = node.read().unwrap().next_inner(0);
+            if !keep {
+                let update = self.trace_node(&node);
+                self.unlink(update, node);
+            }
+        }
+    }
+
+    /// Unlinks `node_to_delete` from every level where `update` points at it
+    /// and returns its entry.
+    fn unlink(&mut self, update: [Link<K, V>; MAX_HEIGHT], node_to_delete: Link<K, V>) -> (K, V) {
         for i in (0..MAX_HEIGHT).rev() {
             let node_to_update = update[MAX_HEIGHT - i - 1].clone();
             let next = node_to_update.read().map(|node| node.next(i)).unwrap();
+            let mut node_to_update_write_lock = node_to_update.write().unwrap();
+            let width = node_to_update_write_lock.width(i);
             match next {
                 Some(arc) if Arc::ptr_eq(&arc, &node_to_delete) => {
-                    let delete_next_i = node_to_delete.read().map(|node| node.next(i)).unwrap();
-
-                    let mut node_to_update_write_lock = node_to_update.write().unwrap();
-                    match delete_next_i {
-                        Some(arc) => {
-                            node_to_update_write_lock.set_next(i, arc);
-                        }
-                        None => {} // Should not happen, None only if node_to_delete is nil
+                    let (delete_next_i, delete_width_i) = node_to_delete
+                        .read()
+                        .map(|node| (node.next(i), node.width(i)))
+                        .unwrap();
+
+                    // None only if node_to_delete is nil, which cannot happen here
+                    if let Some(arc) = delete_next_i {
+                        node_to_update_write_lock.set_next(i, arc, width + delete_width_i - 1);
                     }
                 }
-                _ => continue,
+                // The link passes over the node
+                _ => node_to_update_write_lock.set_width(i, width - 1),
             }
         }
         self.size -= 1;
 
-        loop {
-            match self.header.read().unwrap().next(self.height - 1) {
-                Some(arc) if !arc.read().unwrap().is_nil() && self.height > 1 => {
-                    self.height -= 1;
-                }
-                _ => break,
+        // Drop levels that no longer hold any node, from the header too
+        let mut header_write_lock = self.header.write().unwrap();
+        while self.height > 1 && header_write_lock.next_inner(self.height - 1).is_none() {
+            self.height -= 1;
+        }
+        header_write_lock.lower_height(self.height);
+        drop(header_write_lock);
+
+        // Every predecessor now skips the node, so ours is the last reference
+        let Ok(node) = Arc::try_unwrap(node_to_delete) else {
+            unreachable!("Erased node should no longer be linked");
+        };
+        let node = node.into_inner().unwrap();
+        let born = node.born();
+        let (key, value) = node.into_entry().expect("Only inner nodes can be erased");
+        self.versions.retire(&self.cmp, &key, born);
+        (key, value)
+    }
+
+    /// Counts the nodes whose key equals `key`.
+    pub fn count<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
+        let mut count = 0;
+        let mut cur = self.lower_node(Bound::Included(key));
+        while let Some(node) = cur {
+            let node_read_lock = node.read().unwrap();
+            if self.compare_key(&node_read_lock, key) != Some(Ordering::Equal) {
+                break;
             }
+            count += 1;
+            cur = node_read_lock.next_inner(0);
         }
-        true
+        count
     }
 
-    pub fn contains<Key>(&self, key: Key) -> bool
+    pub fn contains<Q>(&self, key: &Q) -> bool
     where
-        Key: Borrow<K>,
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
     {
-        return self.find(key.borrow()).is_some();
+        self.find(key).is_some()
     }
 
-    fn find<Key: Borrow<K>>(&self, key: Key) -> Option<Arc<RwLock<Node<K>>>> {
+    pub fn find<Q>(&self, key: &Q) -> Option<Link<K, V>>
+    where
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
         let mut cur = self.header.clone();
         let height = self.height;
         for level in (0..height).rev() {
@@ -235,7 +1341,7 @@ impl<K: Ord + Debug, const MAX_HEIGHT: usize, const SEED: u32> SkipListInner<K,
                     }
                 };
                 let next_read_lock = next.read().unwrap();
-                match next_read_lock.compare_key(key.borrow()) {
+                match self.compare_key(&next_read_lock, key) {
                     Some(Ordering::Less) => cur = next.clone(),
                     Some(Ordering::Equal) => return Some(next.clone()),
                     _ => break,
@@ -245,35 +1351,365 @@ impl<K: Ord + Debug, const MAX_HEIGHT: usize, const SEED: u32> SkipListInner<K,
         None
     }
 
-    fn random_height(&self) -> usize {
-        let mut height: usize = 1;
-        let mut rng = self.rng.write().unwrap();
-        while height < MAX_HEIGHT && rng.next_u32() % 4 == 0 {
-            height += 1;
+    /// Returns the rightmost node whose key satisfies `before`, or the header
+    /// if there is none, along with its position counting the header as 0.
+    /// `before` must hold for a prefix of the keys.
+    fn descend(&self, before: impl Fn(&K) -> bool) -> (Link<K, V>, usize) {
+        let mut cur = self.header.clone();
+        let mut rank = 0;
+        for level in (0..self.height).rev() {
+            loop {
+                let (next, width) = {
+                    let cur_read_lock = cur.read().unwrap();
+                    match cur_read_lock.next(level) {
+                        Some(arc) => (arc, cur_read_lock.width(level)),
+                        None => break,
+                    }
+                };
+                if !next.read().unwrap().key().is_some_and(&before) {
+                    break;
+                }
+                cur = next;
+                rank += width;
+            }
+        }
+        (cur, rank)
+    }
+
+    /// Returns the number of keys less than `key`.
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
+        self.descend(|k| self.cmp.compare(k.borrow(), key).is_lt())
+            .1
+    }
+
+    /// Returns the number of keys between `start` and `end`.
+    pub fn count_range<Q>(&self, start: Bound<&Q>, end: Bound<&Q>) -> usize
+    where
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
+        let before = match start {
+            Bound::Included(key) => self.rank(key),
+            Bound::Excluded(key) => {
+                self.descend(|k| self.cmp.compare(k.borrow(), key).is_le())
+                    .1
+            }
+            Bound::Unbounded => 0,
+        };
+        let through = match end {
+            Bound::Included(key) => {
+                self.descend(|k| self.cmp.compare(k.borrow(), key).is_le())
+                    .1
+            }
+            Bound::Excluded(key) => self.rank(key),
+            Bound::Unbounded => self.size,
+        };
+        through.saturating_sub(before)
+    }
+
+    /// Returns the node at `index` in key order, following links only while
+    /// their widths stay within it.
+    pub fn select(&self, index: usize) -> Option<Link<K, V>> {
+        if index >= self.size {
+            return None;
+        }
+        let position = index + 1;
+        let mut cur = self.header.clone();
+        let mut rank = 0;
+        for level in (0..self.height).rev() {
+            loop {
+                let next = {
+                    let cur_read_lock = cur.read().unwrap();
+                    let width = cur_read_lock.width(level);
+                    if rank + width > position {
+                        break;
+                    }
+                    rank += width;
+                    cur_read_lock
+                        .next(level)
+                        .expect("Links below the list height should be set")
+                };
+                cur = next;
+            }
+        }
+        Some(cur)
+    }
+
+    /// Returns the first node inside `bound` when it is read as a lower bound.
+    fn lower_node<Q>(&self, bound: Bound<&Q>) -> Option<Link<K, V>>
+    where
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
+        let before = match bound {
+            Bound::Included(key) => {
+                self.descend(|k| self.cmp.compare(k.borrow(), key).is_lt())
+                    .0
+            }
+            Bound::Excluded(key) => {
+                self.descend(|k| self.cmp.compare(k.borrow(), key).is_le())
+                    .0
+            }
+            Bound::Unbounded => return self.first_node(),
+        };
+        let before_read_lock = before.read().unwrap();
+        before_read_lock.next_inner(0)
+    }
+
+    /// Returns the last node inside `bound` when it is read as an upper bound.
+    fn upper_node<Q>(&self, bound: Bound<&Q>) -> Option<Link<K, V>>
+    where
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
+        let last = match bound {
+            Bound::Included(key) => {
+                self.descend(|k| self.cmp.compare(k.borrow(), key).is_le())
+                    .0
+            }
+            Bound::Excluded(key) => {
+                self.descend(|k| self.cmp.compare(k.borrow(), key).is_lt())
+                    .0
+            }
+            Bound::Unbounded => return self.last_node(),
+        };
+        self.skip_header(last)
+    }
+
+    fn first_node(&self) -> Option<Link<K, V>> {
+        self.header.read().unwrap().next_inner(0)
+    }
+
+    fn last_node(&self) -> Option<Link<K, V>> {
+        self.skip_header(self.descend(|_| true).0)
+    }
+
+    /// Returns the node linked right before `node` on level 0.
+    fn predecessor(&self, node: &Link<K, V>) -> Option<Link<K, V>> {
+        let update = self.trace_node(node);
+        self.skip_header(update[MAX_HEIGHT - 1].clone())
+    }
+
+    /// Like [`trace`](Self::trace), but locates a node that is known to be
+    /// linked, so it also finds the exact predecessors among equal keys.
+    fn trace_node(&self, node: &Link<K, V>) -> [Link<K, V>; MAX_HEIGHT] {
+        let node_read_lock = node.read().unwrap();
+        let key = node_read_lock
+            .key()
+            .expect("Only inner nodes can be traced");
+        let node_height = node_read_lock.height();
+        let mut cur = self.header.clone();
+        array::from_fn(|i| {
+            let level = MAX_HEIGHT - i - 1;
+            loop {
+                let next = match cur.read().unwrap().next_inner(level) {
+                    Some(arc) if !Arc::ptr_eq(&arc, node) => arc,
+                    _ => break,
+                };
+                let advance = match self.compare_key(&next.read().unwrap(), key) {
+                    Some(Ordering::Less) => true,
+                    // Equal keys can only be skipped on levels where `node`
+                    // is linked, otherwise the walk could overshoot it
+                    Some(Ordering::Equal) => level < node_height,
+                    _ => false,
+                };
+                if !advance {
+                    break;
+                }
+                cur = next;
+            }
+            cur.clone()
+        })
+    }
+
+    /// Compares the key of `node` with `key`, or returns `None` for the
+    /// header and `Nil`.
+    fn compare_key<Q>(&self, node: &Node<K, V>, key: &Q) -> Option<Ordering>
+    where
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
+        node.key()
+            .map(|node_key| self.cmp.compare(node_key.borrow(), key))
+    }
+
+    fn skip_header(&self, node: Link<K, V>) -> Option<Link<K, V>> {
+        if Arc::ptr_eq(&node, &self.header) {
+            None
+        } else {
+            Some(node)
         }
-        height
+    }
+
+    pub fn start_recording(&mut self) {
+        self.recorded = Some(Vec::new());
+    }
+
+    pub fn recorded_heights(&self) -> Option<&[usize]> {
+        self.recorded.as_deref()
+    }
+
+    pub fn stop_recording(&mut self) -> Option<Vec<usize>> {
+        self.recorded.take()
+    }
+
+    fn random_height(&mut self) -> usize {
+        self.heights.next_height(self.max_height)
     }
 
     pub fn clear(&mut self) {
-        {
+        self.retire_all();
+        self.unlink_all();
+        self.height = 1;
+        self.size = 0;
+    }
+
+    /// Empties the list and returns its entries in order.
+    pub fn drain(&mut self) -> Vec<(K, V)> {
+        self.retire_all();
+        let mut entries = Vec::with_capacity(self.size);
+        self.unlink_each(|entry| entries.push(entry));
+        self.height = 1;
+        self.size = 0;
+        entries
+    }
+
+    /// Copies aside the keys that live snapshots see before every node is
+    /// unlinked.
+    fn retire_all(&mut self) {
+        if !self.versions.any_live() {
+            return;
+        }
+        let mut cur = self.first_node();
+        while let Some(node) = cur {
+            let node_read_lock = node.read().unwrap();
+            if let Some(key) = node_read_lock.key() {
+                self.versions.retire(&self.cmp, key, node_read_lock.born());
+            }
+            cur = node_read_lock.next_inner(0);
+        }
+    }
+
+    /// Estimates the bytes allocated for the header, every node and the
+    /// shared `Nil`: one `Arc<RwLock<Node>>` allocation each, plus the
+    /// node's vectors of links and widths.
+    pub fn allocated_bytes(&self) -> usize {
+        let node_bytes = |node: &Node<K, V>| {
+            let (links, widths) = match node {
+                Node::Header { links, widths, .. } | Node::Inner { links, widths, .. } => {
+                    (links.capacity(), widths.capacity())
+                }
+                Node::Nil => (0, 0),
+            };
+            // The strong and weak counts precede the value in an `Arc`
+            2 * mem::size_of::<usize>()
+                + mem::size_of::<RwLock<Node<K, V>>>()
+                + links * mem::size_of::<Link<K, V>>()
+                + widths * mem::size_of::<usize>()
+        };
+
+        let mut bytes = node_bytes(&Node::Nil);
+        let mut cur = Some(self.header.clone());
+        while let Some(node) = cur {
+            let node_read_lock = node.read().unwrap();
+            bytes += node_bytes(&node_read_lock);
+            cur = node_read_lock.next_inner(0);
+        }
+        bytes
+    }
+}
+
+impl<K, V, const MAX_HEIGHT: usize, const SEED: u32, C, H>
+    SkipListInner<K, V, MAX_HEIGHT, SEED, C, H>
+{
+    /// Empties the list by clearing the links of one node at a time along
+    /// level 0. Just resetting the header would drop the chain of nodes
+    /// recursively, which overflows the stack on long lists.
+    fn unlink_all(&mut self) {
+        self.unlink_each(drop);
+    }
+
+    /// Like [`unlink_all`](Self::unlink_all), but moves every entry out to
+    /// `f` in order.
+    fn unlink_each(&mut self, mut f: impl FnMut((K, V))) {
+        let mut cur = {
             let mut header = self.header.write().unwrap();
+            let first = header.next(0);
             header.clear();
+            first
+        };
+        // Every predecessor of `node` is already cleared, so dropping it
+        // cannot cascade, and once its own links are cleared we hold the
+        // last reference to it
+        while let Some(node) = cur {
+            let mut node_write_lock = node.write().unwrap();
+            cur = node_write_lock.next(0);
+            node_write_lock.clear();
+            drop(node_write_lock);
+            if let Some(entry) = Arc::try_unwrap(node)
+                .ok()
+                .and_then(|node| node.into_inner().unwrap().into_entry())
+            {
+                f(entry);
+            }
         }
-        self.height = 1;
-        self.size = 0;
     }
 }
 
-impl<K: Ord + std::fmt::Debug, const MAX_HEIGHT: usize, const SEED: u32> Display
-    for SkipListInner<K, MAX_HEIGHT, SEED>
+impl<K, V, const MAX_HEIGHT: usize, const SEED: u32, C, H> Drop
+    for SkipListInner<K, V, MAX_HEIGHT, SEED, C, H>
+{
+    fn drop(&mut self) {
+        self.unlink_all();
+    }
+}
+
+/// Copies every entry with the height of its tower, so the copy has the same
+/// layout. The copy draws new heights from a copy of the generator and does
+/// not inherit a recording.
+impl<K, V, const MAX_HEIGHT: usize, const SEED: u32, C, H> Clone
+    for SkipListInner<K, V, MAX_HEIGHT, SEED, C, H>
+where
+    K: Clone,
+    V: Clone,
+    C: Comparator<K> + Clone,
+    H: HeightGenerator + Clone,
+{
+    fn clone(&self) -> Self {
+        let mut copy =
+            Self::with_generator(self.heights.clone(), self.max_height, self.cmp.clone());
+        copy.capacity = self.capacity;
+        let mut cur = self.first_node();
+        let towers = std::iter::from_fn(|| {
+            let node = cur.take()?;
+            let node_read_lock = node.read().unwrap();
+            cur = node_read_lock.next_inner(0);
+            let (key, value) = node_read_lock.entry()?;
+            Some((key.clone(), value.clone(), Some(node_read_lock.height())))
+        });
+        copy.extend_towers(towers)
+            .expect("A copy of a sorted list is sorted and fits");
+        copy
+    }
+}
+
+impl<K: std::fmt::Debug, V, const MAX_HEIGHT: usize, const SEED: u32, C, H> Display
+    for SkipListInner<K, V, MAX_HEIGHT, SEED, C, H>
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let Err(e) = f.write_fmt(format_args!(
+        f.write_fmt(format_args!(
             "Height: {} | Size: {}\n",
             self.height, self.size
-        )) {
-            return Err(e);
-        };
+        ))?;
         let mut cur = self.header.clone();
         f.write_fmt(format_args!("{}, ", cur.read().unwrap()))?;
         loop {
@@ -281,9 +1717,7 @@ impl<K: Ord + std::fmt::Debug, const MAX_HEIGHT: usize, const SEED: u32> Display
             match next {
                 Some(arc) => {
                     let read_lock = arc.read().unwrap();
-                    if let Err(e) = f.write_fmt(format_args!("{}, ", read_lock,)) {
-                        return Err(e);
-                    }
+                    f.write_fmt(format_args!("{}, ", read_lock))?;
                     cur = arc.clone();
                 }
                 None => break,
@@ -293,60 +1727,140 @@ impl<K: Ord + std::fmt::Debug, const MAX_HEIGHT: usize, const SEED: u32> Display
     }
 }
 
-impl<K: Ord + std::fmt::Debug, const MAX_HEIGHT: usize, const SEED: u32> Debug
-    for SkipListInner<K, MAX_HEIGHT, SEED>
+impl<K: std::fmt::Debug, V, const MAX_HEIGHT: usize, const SEED: u32, C, H> Debug
+    for SkipListInner<K, V, MAX_HEIGHT, SEED, C, H>
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("{self}"))
     }
 }
 
-enum Node<K: Ord> {
+/// Resolves the bounds of `range`, panicking like `BTreeSet::range` if the
+/// start lies after the end or both are equal and excluded.
+pub(crate) fn checked_bounds<'r, Q: ?Sized>(
+    range: &'r impl RangeBounds<Q>,
+    cmp: &impl Comparator<Q>,
+    collection: &str,
+) -> (Bound<&'r Q>, Bound<&'r Q>) {
+    let start = range.start_bound();
+    let end = range.end_bound();
+    match (start, end) {
+        (Bound::Excluded(s), Bound::Excluded(e)) if cmp.compare(s, e).is_eq() => {
+            panic!("range start and end are equal and excluded in {collection}")
+        }
+        (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e))
+            if cmp.compare(s, e).is_gt() =>
+        {
+            panic!("range start is greater than range end in {collection}")
+        }
+        _ => {}
+    }
+    (start, end)
+}
+
+pub(crate) type Link<K, V = ()> = Arc<RwLock<Node<K, V>>>;
+
+/// A node of the list. Next to each link, `widths` holds the number of
+/// level-0 steps the link spans, counting the `Nil` that ends a level as one
+/// past the last node, so positions can be summed up on the way down.
+pub(crate) enum Node<K, V = ()> {
     Header {
         height: usize,
-        links: Vec<Arc<RwLock<Node<K>>>>,
+        links: Vec<Link<K, V>>,
+        widths: Vec<usize>,
     },
     Inner {
         height: usize,
         key: K,
-        links: Vec<Arc<RwLock<Node<K>>>>,
+        value: V,
+        links: Vec<Link<K, V>>,
+        widths: Vec<usize>,
+        /// The version the node was linked in; see [`Snapshot`].
+        born: u64,
     },
     Nil,
 }
 
-impl<K: Ord + Debug> Display for Node<K> {
+impl<K: Debug, V> Display for Node<K, V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Node::Header { height, links: _ } => f.write_fmt(format_args!("[H {}]", height)),
-            Node::Inner { key, height, links: _ } => {
-                f.write_fmt(format_args!("[{:?} {}]", key, height))
-            }
+            Node::Header { height, .. } => f.write_fmt(format_args!("[H {}]", height)),
+            Node::Inner { key, height, .. } => f.write_fmt(format_args!("[{:?} {}]", key, height)),
             Node::Nil => f.write_str("NIL"),
         }
     }
 }
 
-impl<K: Ord + Debug> Debug for Node<K> {
+impl<K: Debug, V> Debug for Node<K, V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("{self}"))
     }
 }
 
-impl<K: Ord + Debug> Node<K> {
+impl<K, V> Node<K, V> {
+    fn into_entry(self) -> Option<(K, V)> {
+        match self {
+            Node::Inner { key, value, .. } => Some((key, value)),
+            _ => None,
+        }
+    }
+
+    fn next(&self, level: usize) -> Option<Link<K, V>> {
+        match self {
+            Node::Nil => None,
+            Node::Header { links, .. } => links.get(level).cloned(),
+            Node::Inner { links, .. } => links.get(level).cloned(),
+        }
+    }
+
+    fn clear(&mut self) {
+        match self {
+            Node::Header {
+                links,
+                widths,
+                height,
+            } => {
+                links.fill(Arc::new(RwLock::new(Node::Nil)));
+                widths.fill(1);
+                *height = 1;
+            }
+            Node::Inner {
+                links,
+                widths,
+                height,
+                ..
+            } => {
+                links.clear();
+                widths.clear();
+                *height = 0;
+            }
+            Node::Nil => {}
+        }
+    }
+}
+
+impl<K, V> Node<K, V> {
     fn new_header(height: usize) -> Self {
         let mut links = Vec::with_capacity(height);
         let nil = Arc::new(RwLock::new(Self::Nil));
         for _ in 0..height {
             links.push(nil.clone());
         }
-        Self::Header { height: 1, links: links }
+        Self::Header {
+            height: 1,
+            links,
+            widths: vec![1; height],
+        }
     }
 
-    fn new(key: K, height: usize) -> Self {
+    fn new(key: K, value: V, height: usize, born: u64) -> Self {
         Self::Inner {
             height: 0,
-            key: key,
+            key,
+            value,
             links: Vec::with_capacity(height),
+            widths: Vec::with_capacity(height),
+            born,
         }
     }
 
@@ -358,64 +1872,119 @@ impl<K: Ord + Debug> Node<K> {
         }
     }
 
-    fn next(&self, level: usize) -> Option<Arc<RwLock<Node<K>>>> {
-        match self {
-            Node::Nil => None,
-            Node::Header { links, .. } => links.get(level).cloned(),
-            Node::Inner { links, .. } => links.get(level).cloned(),
+    /// Like [`Node::next`], but treats the trailing `Nil` as the end of the level.
+    fn next_inner(&self, level: usize) -> Option<Link<K, V>> {
+        self.next(level)
+            .filter(|next| !next.read().unwrap().is_nil())
+    }
+
+    /// Lowers the header's height to `new_height`, once the levels above it
+    /// are empty. Other nodes keep their towers.
+    fn lower_height(&mut self, new_height: usize) {
+        if let Node::Header { height, .. } = self {
+            *height = new_height;
         }
     }
 
-    fn set_next(&mut self, level: usize, next: Arc<RwLock<Node<K>>>) {
+    /// Links `next` on `level`, `width` level-0 steps ahead.
+    fn set_next(&mut self, level: usize, next: Link<K, V>, width: usize) {
         match self {
-            Node::Header { height, links } => {
+            Node::Header {
+                height,
+                links,
+                widths,
+            } => {
                 if *height < level + 1 {
                     *height = level + 1;
                 }
                 links[level] = next;
+                widths[level] = width;
             }
-            Node::Inner { height, links, .. } => {
+            Node::Inner {
+                height,
+                links,
+                widths,
+                ..
+            } => {
                 if *height < level + 1 {
                     *height = level + 1;
                 }
                 if level < links.len() {
                     links[level] = next;
+                    widths[level] = width;
                 } else {
                     links.resize(level + 1, next);
+                    widths.resize(level + 1, width);
                 }
             }
             Node::Nil => {}
         }
     }
 
-    fn compare_key(&self, key: impl Borrow<K>) -> Option<Ordering> {
+    /// Returns the number of level-0 steps the link on `level` spans.
+    fn width(&self, level: usize) -> usize {
         match self {
-            Node::Header { .. } => None,
-            Node::Inner {
-                key: node_key,
-                ..
-            } => Some(node_key.cmp(key.borrow())),
-            Node::Nil => None,
+            Node::Header { widths, .. } | Node::Inner { widths, .. } => widths[level],
+            Node::Nil => 0,
         }
     }
 
-    fn clear(&mut self) {
+    fn set_width(&mut self, level: usize, width: usize) {
         match self {
-            Node::Header { links, height, .. } => {
-                links.fill(Arc::new(RwLock::new(Node::Nil)));
-                *height = 1;
-            }
-            Node::Inner { links, height, .. } => {
-                links.clear();
-                *height = 0;
-            },
+            Node::Header { widths, .. } | Node::Inner { widths, .. } => widths[level] = width,
             Node::Nil => {}
         }
     }
 
+    fn born(&self) -> u64 {
+        match self {
+            Node::Inner { born, .. } => *born,
+            _ => 0,
+        }
+    }
+
     fn is_nil(&self) -> bool {
         matches!(self, Node::Nil)
     }
+
+    pub fn key(&self) -> Option<&K> {
+        match self {
+            Node::Inner { key, .. } => Some(key),
+            _ => None,
+        }
+    }
+
+    pub fn entry(&self) -> Option<(&K, &V)> {
+        match self {
+            Node::Inner { key, value, .. } => Some((key, value)),
+            _ => None,
+        }
+    }
+
+    fn entry_mut(&mut self) -> Option<(&K, &mut V)> {
+        match self {
+            Node::Inner { key, value, .. } => Some((key, value)),
+            _ => None,
+        }
+    }
+
+    pub fn value(&self) -> Option<&V> {
+        match self {
+            Node::Inner { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn value_mut(&mut self) -> Option<&mut V> {
+        match self {
+            Node::Inner { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    fn replace_value(&mut self, value: V) -> Option<V> {
+        self.value_mut().map(|old| std::mem::replace(old, value))
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,53 @@
+use std::cmp::Ordering;
+
+/// Orders the keys of a [`SkipList`](crate::SkipList).
+///
+/// Every search, insert and erase goes through the list's comparator, which
+/// must be a total order and must not change while the list is in use.
+/// [`OrdComparator`], the default, defers to `Ord`; any
+/// `Fn(&K, &K) -> Ordering` closure is a comparator as well.
+///
+/// Lookups by a borrowed form `Q` of the key, such as `&str` for `String`
+/// keys, need the comparator to implement `Comparator<Q>` too, ordering the
+/// borrowed forms exactly as it orders the keys.
+///
+/// ```
+/// use std::cmp::Ordering;
+///
+/// use p0::{Comparator, SkipList};
+///
+/// struct CaseInsensitive;
+///
+/// impl<K: AsRef<str> + ?Sized> Comparator<K> for CaseInsensitive {
+///     fn compare(&self, a: &K, b: &K) -> Ordering {
+///         a.as_ref().to_lowercase().cmp(&b.as_ref().to_lowercase())
+///     }
+/// }
+///
+/// let list = SkipList::<String>::with_comparator(CaseInsensitive);
+/// assert!(list.insert("Apple".to_string()));
+/// assert!(!list.insert("APPLE".to_string()));
+/// assert!(list.contains("apple"));
+/// ```
+pub trait Comparator<K: ?Sized> {
+    fn compare(&self, a: &K, b: &K) -> Ordering;
+}
+
+/// Orders keys by their `Ord` implementation.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OrdComparator;
+
+impl<K: Ord + ?Sized> Comparator<K> for OrdComparator {
+    fn compare(&self, a: &K, b: &K) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+impl<K: ?Sized, F> Comparator<K> for F
+where
+    F: Fn(&K, &K) -> Ordering,
+{
+    fn compare(&self, a: &K, b: &K) -> Ordering {
+        self(a, b)
+    }
+}
@@ -0,0 +1,163 @@
+use std::{error::Error, fmt::Display};
+
+/// Runtime parameters for a [`SkipList`](crate::SkipList) or
+/// [`SkipMap`](crate::SkipMap), passed to their `with_config` constructors.
+///
+/// Every parameter left unset falls back to the list's const-generic
+/// defaults, so `with_config(SkipListConfig::new())` builds the same list
+/// as `new()`.
+///
+/// ```
+/// use p0::{SkipList, SkipListConfig};
+///
+/// let config = SkipListConfig::new().p(0.5).max_height(8).seed(42);
+/// let list = SkipList::<i32>::with_config(config).unwrap();
+/// assert!(list.insert(1));
+///
+/// let invalid = SkipListConfig::new().p(1.5);
+/// assert!(SkipList::<i32>::with_config(invalid).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SkipListConfig {
+    p: f64,
+    max_height: Option<usize>,
+    seed: Option<u32>,
+    capacity: Option<usize>,
+}
+
+impl SkipListConfig {
+    /// Creates a config with the default parameters: p = 1/4, and the
+    /// list's `MAX_HEIGHT` and `SEED`.
+    pub fn new() -> Self {
+        SkipListConfig {
+            p: 0.25,
+            max_height: None,
+            seed: None,
+            capacity: None,
+        }
+    }
+
+    /// Sets the probability that a tower grows one more level. Must lie
+    /// strictly between 0 and 1.
+    pub fn p(mut self, p: f64) -> Self {
+        self.p = p;
+        self
+    }
+
+    /// Sets the maximum tower height. Must be at least 1 and at most the
+    /// list's `MAX_HEIGHT`, which sizes its search buffers.
+    pub fn max_height(mut self, max_height: usize) -> Self {
+        self.max_height = Some(max_height);
+        self
+    }
+
+    /// Sets the seed of the height generator.
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Caps the number of keys the list holds. Inserting a new key into a
+    /// full list fails with
+    /// [`SkipListError::CapacityExceeded`](crate::SkipListError::CapacityExceeded)
+    /// through the `try_` methods, and panics otherwise. Unbounded by default.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Checks the parameters against a list of capacity `MAX_HEIGHT` and
+    /// resolves the unset ones.
+    pub(crate) fn resolve<const MAX_HEIGHT: usize, const SEED: u32>(
+        self,
+    ) -> Result<Resolved, ConfigError> {
+        let promotion = Promotion::from_p(self.p)?;
+        let max_height = self.max_height.unwrap_or(MAX_HEIGHT);
+        if max_height == 0 || max_height > MAX_HEIGHT {
+            return Err(ConfigError::InvalidMaxHeight {
+                max_height,
+                capacity: MAX_HEIGHT,
+            });
+        }
+        Ok(Resolved {
+            promotion,
+            max_height,
+            seed: self.seed.unwrap_or(SEED),
+            capacity: self.capacity,
+        })
+    }
+}
+
+impl Default for SkipListConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A validated [`SkipListConfig`].
+pub(crate) struct Resolved {
+    pub promotion: Promotion,
+    pub max_height: usize,
+    pub seed: u32,
+    pub capacity: Option<usize>,
+}
+
+/// Decides from one random draw whether a tower grows another level.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Promotion {
+    /// Promote when the draw is a multiple of `n`, i.e. with p = 1/n. This
+    /// is how the defaults have always drawn heights.
+    OneIn(u32),
+    /// Promote when the draw falls below the threshold.
+    Below(u32),
+}
+
+impl Promotion {
+    pub fn from_p(p: f64) -> Result<Self, ConfigError> {
+        if !(p > 0.0 && p < 1.0) {
+            return Err(ConfigError::InvalidProbability(p));
+        }
+        let inverse = 1.0 / p;
+        if inverse.fract() == 0.0 && inverse <= u32::MAX as f64 {
+            Ok(Promotion::OneIn(inverse as u32))
+        } else {
+            Ok(Promotion::Below((p * (1u64 << 32) as f64) as u32))
+        }
+    }
+
+    pub fn promote(self, draw: u32) -> bool {
+        match self {
+            Promotion::OneIn(n) => draw.is_multiple_of(n),
+            Promotion::Below(threshold) => draw < threshold,
+        }
+    }
+}
+
+/// Why a [`SkipListConfig`] was rejected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ConfigError {
+    /// The promotion probability is not strictly between 0 and 1.
+    InvalidProbability(f64),
+    /// The maximum height is 0 or above the list's `MAX_HEIGHT`.
+    #[non_exhaustive]
+    InvalidMaxHeight { max_height: usize, capacity: usize },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::InvalidProbability(p) => {
+                f.write_fmt(format_args!("probability {p} is not between 0 and 1"))
+            }
+            ConfigError::InvalidMaxHeight {
+                max_height,
+                capacity,
+            } => f.write_fmt(format_args!(
+                "max height {max_height} is not between 1 and {capacity}"
+            )),
+        }
+    }
+}
+
+impl Error for ConfigError {}
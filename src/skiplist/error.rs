@@ -0,0 +1,79 @@
+use std::{error::Error, fmt::Display};
+
+/// Why a fallible [`SkipList`](crate::SkipList) operation such as
+/// [`try_insert`](crate::SkipList::try_insert) failed.
+///
+/// A thread that panics while writing to the list, say in the key's `Ord`
+/// implementation, poisons it. From then on every `try_` call reports
+/// [`Poisoned`](Self::Poisoned) and every other call panics. The way back
+/// is [`recover`](crate::SkipList::recover), which clears the poison and
+/// revalidates the structure. Writers only relink nodes once their search
+/// is over, so a panic in `Ord` leaves the list intact; should validation
+/// still report a violation, [`clear`](crate::SkipList::clear) the list and
+/// rebuild it.
+///
+/// ```
+/// use std::cmp::Ordering;
+/// use std::panic::{self, AssertUnwindSafe};
+///
+/// use p0::{SkipList, SkipListError};
+///
+/// #[derive(Clone, Debug, PartialEq, Eq)]
+/// struct Key(i32);
+///
+/// impl Ord for Key {
+///     fn cmp(&self, other: &Self) -> Ordering {
+///         assert!(self.0 >= 0 && other.0 >= 0, "negative key");
+///         self.0.cmp(&other.0)
+///     }
+/// }
+///
+/// impl PartialOrd for Key {
+///     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+///         Some(self.cmp(other))
+///     }
+/// }
+///
+/// let list = SkipList::<Key>::new();
+/// list.insert(Key(1));
+/// assert!(panic::catch_unwind(AssertUnwindSafe(|| list.insert(Key(-1)))).is_err());
+/// assert_eq!(list.try_contains(&Key(1)), Err(SkipListError::Poisoned));
+///
+/// if list.recover().is_err() {
+///     list.clear();
+/// }
+/// assert_eq!(list.try_insert(Key(2)), Ok(true));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SkipListError {
+    /// A thread panicked while holding the list's write lock.
+    Poisoned,
+    /// The list already holds `capacity` keys; see
+    /// [`SkipListConfig::capacity`](crate::SkipListConfig::capacity).
+    #[non_exhaustive]
+    CapacityExceeded { capacity: usize },
+    /// The key at `index` of the input to
+    /// [`extend_sorted`](crate::SkipList::extend_sorted) is smaller than
+    /// the one before it.
+    #[non_exhaustive]
+    Unsorted { index: usize },
+}
+
+impl Display for SkipListError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipListError::Poisoned => {
+                f.write_str("the skiplist was poisoned by a panicking writer")
+            }
+            SkipListError::CapacityExceeded { capacity } => f.write_fmt(format_args!(
+                "the skiplist is full at its capacity of {capacity} keys"
+            )),
+            SkipListError::Unsorted { index } => f.write_fmt(format_args!(
+                "sorted input is out of order at index {index}"
+            )),
+        }
+    }
+}
+
+impl Error for SkipListError {}
@@ -0,0 +1,234 @@
+use rand_core::{impls, RngCore};
+use std::{
+    cell::Cell,
+    collections::hash_map::RandomState,
+    hash::BuildHasher,
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+};
+
+use mt19937::MT19937;
+
+use super::config::{ConfigError, Promotion};
+
+/// Draws the heights of new towers.
+///
+/// A list asks its generator once per inserted key, while holding the write
+/// lock, so implementations need no synchronization of their own.
+pub trait HeightGenerator {
+    /// Returns the height of the next tower, between 1 and `max_height`.
+    fn next_height(&mut self, max_height: usize) -> usize;
+}
+
+/// Lets a list pick its generator at runtime, as a
+/// `Box<dyn HeightGenerator + Send + Sync>`, at the cost of a virtual call
+/// per insert.
+impl<G: HeightGenerator + ?Sized> HeightGenerator for Box<G> {
+    fn next_height(&mut self, max_height: usize) -> usize {
+        (**self).next_height(max_height)
+    }
+}
+
+/// Grows each tower one level at a time with probability p, drawing from
+/// any [`RngCore`], as re-exported at [`p0::rand_core`](crate::rand_core).
+///
+/// ```
+/// use p0::{Mt19937Heights, SkipList};
+///
+/// // The generator `SkipList::new` uses
+/// let list = SkipList::<i32>::with_generator(Mt19937Heights::seeded(15445));
+/// assert!(list.insert(1));
+/// ```
+pub struct RngHeights<R: RngCore> {
+    rng: R,
+    promotion: Promotion,
+}
+
+impl<R: RngCore> RngHeights<R> {
+    /// Wraps `rng` with a promotion probability of 1/4.
+    pub fn new(rng: R) -> Self {
+        RngHeights {
+            rng,
+            promotion: Promotion::OneIn(4),
+        }
+    }
+
+    /// Wraps `rng` with a promotion probability of `p`, which must lie
+    /// strictly between 0 and 1.
+    pub fn with_p(rng: R, p: f64) -> Result<Self, ConfigError> {
+        Ok(RngHeights {
+            rng,
+            promotion: Promotion::from_p(p)?,
+        })
+    }
+
+    pub(crate) fn with_promotion(rng: R, promotion: Promotion) -> Self {
+        RngHeights { rng, promotion }
+    }
+}
+
+impl<R: RngCore> HeightGenerator for RngHeights<R> {
+    fn next_height(&mut self, max_height: usize) -> usize {
+        let mut height: usize = 1;
+        while height < max_height && self.promotion.promote(self.rng.next_u32()) {
+            height += 1;
+        }
+        height
+    }
+}
+
+/// The seeded MT19937 generator behind [`SkipList::new`](crate::SkipList::new).
+pub type Mt19937Heights = RngHeights<MT19937>;
+
+impl RngHeights<MT19937> {
+    /// Seeds MT19937 with `seed`. With the list's default `SEED` this draws
+    /// the same heights as [`SkipList::new`](crate::SkipList::new).
+    pub fn seeded(seed: u32) -> Self {
+        Self::new(MT19937::new_with_slice_seed(&[seed]))
+    }
+}
+
+/// A copy draws the same heights as the original from here on. `MT19937`
+/// is not `Clone` itself, so its state is copied by hand.
+impl Clone for RngHeights<MT19937> {
+    fn clone(&self) -> Self {
+        let mut rng = MT19937::default();
+        rng.set_state(self.rng.get_state());
+        rng.set_index(self.rng.get_index());
+        RngHeights {
+            rng,
+            promotion: self.promotion,
+        }
+    }
+}
+
+/// Heights drawn from a per-thread xorshift generator; see [`ThreadLocalRng`].
+pub type ThreadLocalHeights = RngHeights<ThreadLocalRng>;
+
+impl Clone for RngHeights<ThreadLocalRng> {
+    fn clone(&self) -> Self {
+        RngHeights {
+            rng: ThreadLocalRng,
+            promotion: self.promotion,
+        }
+    }
+}
+
+thread_local! {
+    static XORSHIFT: Cell<u64> = Cell::new(thread_seed());
+}
+
+fn thread_seed() -> u64 {
+    // Xorshift never leaves the all-zero state
+    RandomState::new().hash_one(thread::current().id()) | 1
+}
+
+/// A handle to a fast xorshift64* generator kept in thread-local storage
+/// and randomly seeded once per thread.
+///
+/// Its output is not reproducible, but drawing from it costs a few cycles
+/// and never touches state shared with other threads.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ThreadLocalRng;
+
+impl RngCore for ThreadLocalRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        XORSHIFT.with(|state| {
+            let mut x = state.get();
+            x ^= x >> 12;
+            x ^= x << 25;
+            x ^= x >> 27;
+            state.set(x);
+            x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        })
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dst)
+    }
+}
+
+/// A SplitMix64 generator whose state is one atomic counter, for lists
+/// that draw heights from several threads at once without a lock.
+///
+/// Each draw is a single `fetch_add`, so a thread never waits on another.
+/// Draws from one thread follow the seed, which keeps single-threaded
+/// layouts reproducible.
+pub(crate) struct AtomicSplitMix(AtomicU64);
+
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+impl AtomicSplitMix {
+    pub fn new(seed: u32) -> Self {
+        AtomicSplitMix(AtomicU64::new(seed.into()))
+    }
+}
+
+impl RngCore for &AtomicSplitMix {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut z = self
+            .0
+            .fetch_add(GOLDEN_GAMMA, Ordering::Relaxed)
+            .wrapping_add(GOLDEN_GAMMA);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dst)
+    }
+}
+
+/// Hands out a fixed sequence of heights, for tests that need an exact
+/// tower layout.
+///
+/// ```
+/// use p0::{ScriptedHeights, SkipList};
+///
+/// let list = SkipList::<i32>::with_generator(ScriptedHeights::new([1, 3, 2]));
+/// for key in [10, 20, 30] {
+///     list.insert(key);
+/// }
+/// assert_eq!(format!("{list}"), "Height: 3 | Size: 3\n[H 3], [10 1], [20 3], [30 2], NIL, ");
+/// ```
+#[derive(Clone, Debug)]
+pub struct ScriptedHeights {
+    heights: Vec<usize>,
+    next: usize,
+}
+
+impl ScriptedHeights {
+    pub fn new(heights: impl IntoIterator<Item = usize>) -> Self {
+        ScriptedHeights {
+            heights: heights.into_iter().collect(),
+            next: 0,
+        }
+    }
+}
+
+impl HeightGenerator for ScriptedHeights {
+    /// # Panics
+    ///
+    /// Panics once the script is exhausted, or if the next height is not
+    /// between 1 and `max_height`.
+    fn next_height(&mut self, max_height: usize) -> usize {
+        let Some(&height) = self.heights.get(self.next) else {
+            panic!("ScriptedHeights ran out after {} heights", self.next);
+        };
+        assert!(
+            (1..=max_height).contains(&height),
+            "Scripted height {height} is not between 1 and {max_height}"
+        );
+        self.next += 1;
+        height
+    }
+}
@@ -0,0 +1,273 @@
+use std::{
+    borrow::Borrow,
+    iter::FusedIterator,
+    ops::Bound,
+    sync::{Arc, RwLockReadGuard},
+    vec,
+};
+
+use super::{Comparator, HeightGenerator, Link, Mt19937Heights, OrdComparator, SkipListInner};
+
+/// Walks level-0 links between two inclusive end nodes.
+///
+/// The cursor owns a read guard on the whole list, so the structure cannot
+/// change under it and both ends stay linked for as long as it lives.
+pub(crate) struct Cursor<'a, K, V, const MAX_HEIGHT: usize, const SEED: u32, C, H = Mt19937Heights>
+{
+    inner: RwLockReadGuard<'a, SkipListInner<K, V, MAX_HEIGHT, SEED, C, H>>,
+    front: Option<Link<K, V>>,
+    back: Option<Link<K, V>>,
+}
+
+impl<'a, K, V, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator>
+    Cursor<'a, K, V, MAX_HEIGHT, SEED, C, H>
+{
+    pub fn new(inner: RwLockReadGuard<'a, SkipListInner<K, V, MAX_HEIGHT, SEED, C, H>>) -> Self {
+        let front = inner.first_node();
+        let back = inner.last_node();
+        Cursor { inner, front, back }
+    }
+
+    /// Creates a cursor over the nodes whose keys lie between `start` and `end`.
+    pub fn between<Q>(
+        inner: RwLockReadGuard<'a, SkipListInner<K, V, MAX_HEIGHT, SEED, C, H>>,
+        start: Bound<&Q>,
+        end: Bound<&Q>,
+    ) -> Self
+    where
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
+        let front = inner.lower_node(start);
+        let back = inner.upper_node(end);
+        // Both ends may fall into the same gap between two keys
+        let empty = match (&front, &back) {
+            (Some(first), Some(last)) if !Arc::ptr_eq(first, last) => {
+                let first_read_lock = first.read().unwrap();
+                let last_read_lock = last.read().unwrap();
+                match (first_read_lock.key(), last_read_lock.key()) {
+                    (Some(first_key), Some(last_key)) => {
+                        inner.cmp.compare(first_key, last_key).is_gt()
+                    }
+                    _ => false,
+                }
+            }
+            (Some(_), Some(_)) => false,
+            _ => true,
+        };
+        if empty {
+            return Cursor {
+                inner,
+                front: None,
+                back: None,
+            };
+        }
+        Cursor { inner, front, back }
+    }
+
+    pub fn next_with<T>(&mut self, f: impl FnOnce(&K, &V) -> T) -> Option<T> {
+        let node = self.front.take()?;
+        if self
+            .back
+            .as_ref()
+            .is_some_and(|back| Arc::ptr_eq(back, &node))
+        {
+            self.back = None;
+        } else {
+            self.front = node.read().unwrap().next_inner(0);
+        }
+        let node_read_lock = node.read().unwrap();
+        node_read_lock.entry().map(|(key, value)| f(key, value))
+    }
+
+    pub fn next_back_with<T>(&mut self, f: impl FnOnce(&K, &V) -> T) -> Option<T> {
+        let node = self.back.take()?;
+        if self
+            .front
+            .as_ref()
+            .is_some_and(|front| Arc::ptr_eq(front, &node))
+        {
+            self.front = None;
+        } else {
+            self.back = self.inner.predecessor(&node);
+        }
+        let node_read_lock = node.read().unwrap();
+        node_read_lock.entry().map(|(key, value)| f(key, value))
+    }
+}
+
+/// An iterator over the keys of a [`SkipList`](crate::SkipList) or
+/// [`SkipMultiSet`](crate::SkipMultiSet), in ascending order.
+///
+/// The iterator holds a read lock on the list until it is dropped. It
+/// therefore sees the list exactly as it was when created, and writers on
+/// other threads block until it is gone. Calling `insert`, `erase` or
+/// `clear` on the same thread while an iterator is alive deadlocks.
+/// For long scans next to writers, iterate a [`Snapshot`](crate::Snapshot)
+/// instead. Taking a snapshot also needs the write lock, but dropping one
+/// does not.
+///
+/// Keys are cloned out of the list as they are yielded. Walking backwards
+/// costs a top-down search per step, so `next_back` is O(log n) where
+/// `next` is O(1).
+pub struct Iter<
+    'a,
+    K,
+    const MAX_HEIGHT: usize = 14,
+    const SEED: u32 = 15445,
+    C = OrdComparator,
+    H = Mt19937Heights,
+> {
+    cursor: Cursor<'a, K, (), MAX_HEIGHT, SEED, C, H>,
+    remaining: usize,
+}
+
+impl<'a, K, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator>
+    Iter<'a, K, MAX_HEIGHT, SEED, C, H>
+{
+    pub(crate) fn new(
+        inner: RwLockReadGuard<'a, SkipListInner<K, (), MAX_HEIGHT, SEED, C, H>>,
+    ) -> Self {
+        let remaining = inner.size();
+        Iter {
+            cursor: Cursor::new(inner),
+            remaining,
+        }
+    }
+}
+
+impl<K: Clone, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator>
+    Iterator for Iter<'_, K, MAX_HEIGHT, SEED, C, H>
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        let key = self.cursor.next_with(|key, _| key.clone())?;
+        self.remaining -= 1;
+        Some(key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K: Clone, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator>
+    DoubleEndedIterator for Iter<'_, K, MAX_HEIGHT, SEED, C, H>
+{
+    fn next_back(&mut self) -> Option<K> {
+        let key = self.cursor.next_back_with(|key, _| key.clone())?;
+        self.remaining -= 1;
+        Some(key)
+    }
+}
+
+impl<K: Clone, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator>
+    ExactSizeIterator for Iter<'_, K, MAX_HEIGHT, SEED, C, H>
+{
+}
+
+impl<K: Clone, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator>
+    FusedIterator for Iter<'_, K, MAX_HEIGHT, SEED, C, H>
+{
+}
+
+/// An iterator over a sub-range of the keys of a [`SkipList`](crate::SkipList),
+/// in ascending order.
+///
+/// Created by [`SkipList::range`](crate::SkipList::range) and
+/// [`SkipMultiSet::range`](crate::SkipMultiSet::range). Like [`Iter`], it
+/// holds a read lock on the list until it is dropped.
+pub struct Range<
+    'a,
+    K,
+    const MAX_HEIGHT: usize = 14,
+    const SEED: u32 = 15445,
+    C = OrdComparator,
+    H = Mt19937Heights,
+> {
+    cursor: Cursor<'a, K, (), MAX_HEIGHT, SEED, C, H>,
+}
+
+impl<'a, K, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator>
+    Range<'a, K, MAX_HEIGHT, SEED, C, H>
+{
+    pub(crate) fn new<Q>(
+        inner: RwLockReadGuard<'a, SkipListInner<K, (), MAX_HEIGHT, SEED, C, H>>,
+        start: Bound<&Q>,
+        end: Bound<&Q>,
+    ) -> Self
+    where
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
+        Range {
+            cursor: Cursor::between(inner, start, end),
+        }
+    }
+}
+
+impl<K: Clone, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator>
+    Iterator for Range<'_, K, MAX_HEIGHT, SEED, C, H>
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        self.cursor.next_with(|key, _| key.clone())
+    }
+}
+
+impl<K: Clone, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator>
+    DoubleEndedIterator for Range<'_, K, MAX_HEIGHT, SEED, C, H>
+{
+    fn next_back(&mut self) -> Option<K> {
+        self.cursor.next_back_with(|key, _| key.clone())
+    }
+}
+
+impl<K: Clone, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator>
+    FusedIterator for Range<'_, K, MAX_HEIGHT, SEED, C, H>
+{
+}
+
+/// An owning iterator over the keys of a [`SkipList`](crate::SkipList), or
+/// the entries of a [`FrozenMemtable`](crate::FrozenMemtable), in ascending
+/// order.
+///
+/// Created by `into_iter`, which unlinks every node up front, so the items
+/// are moved out rather than cloned and no lock is held.
+pub struct IntoIter<K> {
+    keys: vec::IntoIter<K>,
+}
+
+impl<K> IntoIter<K> {
+    pub(crate) fn new(keys: Vec<K>) -> Self {
+        IntoIter {
+            keys: keys.into_iter(),
+        }
+    }
+}
+
+impl<K> Iterator for IntoIter<K> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        self.keys.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+impl<K> DoubleEndedIterator for IntoIter<K> {
+    fn next_back(&mut self) -> Option<K> {
+        self.keys.next_back()
+    }
+}
+
+impl<K> ExactSizeIterator for IntoIter<K> {}
+
+impl<K> FusedIterator for IntoIter<K> {}
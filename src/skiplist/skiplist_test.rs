@@ -1,16 +1,17 @@
-use std::sync::Barrier;
 use std::sync::Arc;
+use std::sync::Barrier;
 use std::sync::Mutex;
 use std::thread::scope;
 
 use super::*;
+use crate::{ArenaSkipList, LazySkipList, LockFreeSkipList, SkipMap, SkipMultiSet};
 
 trait Check {
-    fn check_integrity(&self, keys: &Vec<i32>, heights: &Vec<usize>);
+    fn check_integrity(&self, keys: &[i32], heights: &[usize]);
 }
 
 impl<const MAX_HEIGHT: usize, const SEED: u32> Check for SkipList<i32, MAX_HEIGHT, SEED> {
-    fn check_integrity(&self, keys: &Vec<i32>, heights: &Vec<usize>) {
+    fn check_integrity(&self, keys: &[i32], heights: &[usize]) {
         assert_eq!(self.size(), keys.len());
         let list = self.inner.read().unwrap();
         let mut pos = 0;
@@ -23,15 +24,17 @@ impl<const MAX_HEIGHT: usize, const SEED: u32> Check for SkipList<i32, MAX_HEIGH
                     let node = curr.read().unwrap();
                     let height = node.height();
                     assert_eq!(height, heights[pos]);
-                    assert_eq!(node.compare_key(keys[pos]), Some(Ordering::Equal));
+                    assert_eq!(list.compare_key(&node, &keys[pos]), Some(Ordering::Equal));
 
                     // Check link at each node level
                     for level in 0..height {
                         for i in (pos + 1)..keys.len() {
                             if heights[i] > level {
                                 assert_eq!(
-                                    node.next(level)
-                                        .and_then(|n| n.read().map(|n| n.compare_key(keys[i])).unwrap()),
+                                    node.next(level).and_then(|n| n
+                                        .read()
+                                        .map(|n| list.compare_key(&n, &keys[i]))
+                                        .unwrap()),
                                     Some(Ordering::Equal)
                                 );
                                 break;
@@ -52,7 +55,9 @@ impl<const MAX_HEIGHT: usize, const SEED: u32> Check for SkipList<i32, MAX_HEIGH
 fn integrity_check_test() {
     let list = SkipList::<i32>::new();
 
-    let mut keys = vec![12, 16, 2, 6, 15, 8, 13, 1, 11, 14, 0, 4, 19, 10, 9, 5, 7, 3, 17, 18];
+    let mut keys = vec![
+        12, 16, 2, 6, 15, 8, 13, 1, 11, 14, 0, 4, 19, 10, 9, 5, 7, 3, 17, 18,
+    ];
     let heights = vec![1, 1, 2, 1, 1, 1, 1, 2, 1, 1, 3, 1, 2, 1, 1, 1, 1, 1, 1, 1];
 
     for key in &keys {
@@ -120,10 +125,10 @@ fn insert_contain_test_2() {
     assert!(list.insert(2));
     assert_eq!(list.size(), 2);
 
-    assert!(list.contains(1));
-    assert!(list.contains(2));
+    assert!(list.contains(&1));
+    assert!(list.contains(&2));
 
-    assert!(!list.contains(3));
+    assert!(!list.contains(&3));
 }
 
 #[test]
@@ -137,8 +142,8 @@ fn insert_and_erase() {
     assert_eq!(list.size(), 5);
 
     for i in 0..5 {
-        assert!(list.contains(i));
-        assert!(list.erase(i));
+        assert!(list.contains(&i));
+        assert!(list.erase(&i));
 
         assert_eq!(list.size(), (5 - i - 1).try_into().unwrap());
     }
@@ -154,7 +159,7 @@ fn erase_non_existing_test() {
         assert!(list.insert(i));
     }
 
-    assert!(!list.erase(10));
+    assert!(!list.erase(&10));
     assert_eq!(list.size(), 5);
 }
 
@@ -193,11 +198,14 @@ fn concurrent_insert_test() {
         }
     });
 
-    assert_eq!(successful_insertion.lock().unwrap().to_owned(), num_threads * num_insertions_per_thread);
+    assert_eq!(
+        successful_insertion.lock().unwrap().to_owned(),
+        num_threads * num_insertions_per_thread
+    );
 
     println!("{list}");
     for i in 0..(num_threads * num_insertions_per_thread) {
-        assert!(list.contains::<i32>(i as i32), "Failed to find key: {}", i);
+        assert!(list.contains(&(i as i32)), "Failed to find key: {}", i);
     }
 }
 
@@ -222,23 +230,25 @@ fn concurrent_erase_test() {
             s.spawn(move || {
                 barrier.wait();
                 for j in 0..num_erasures_per_thread {
-                    if list.erase::<i32>((k + j).try_into().unwrap()) {
+                    if list.erase(&i32::try_from(k + j).unwrap()) {
                         let mut success_erase = successful_erasures.lock().unwrap();
                         *success_erase += 1;
                     }
                 }
-
             });
         }
     });
 
-    assert!(successful_erasures.lock().map(|n| *n == num_erasures_per_thread * num_threads).unwrap());
+    assert!(successful_erasures
+        .lock()
+        .map(|n| *n == num_erasures_per_thread * num_threads)
+        .unwrap());
 
     for i in 0..100 {
         if i < num_threads * num_erasures_per_thread {
-            assert!(!list.contains::<i32>(i.try_into().unwrap()));
+            assert!(!list.contains(&i32::try_from(i).unwrap()));
         } else {
-            assert!(list.contains::<i32>(i.try_into().unwrap()));
+            assert!(list.contains(&i32::try_from(i).unwrap()));
         }
     }
 }
@@ -268,7 +278,7 @@ fn concurrent_insert_and_erase_test() {
             s.spawn(move || {
                 barrier.wait();
                 for j in start..(NUM_OPERATIONS_PER_THREAD + start) {
-                    if !list.contains(j) {
+                    if !list.contains(&j) {
                         list.insert(j);
                     }
 
@@ -277,7 +287,7 @@ fn concurrent_insert_and_erase_test() {
                         *inserts += 1;
                     }
 
-                    if list.erase(j) {
+                    if list.erase(&j) {
                         let mut erases = succ_erases.lock().unwrap();
                         *erases += 1;
                     }
@@ -286,15 +296,21 @@ fn concurrent_insert_and_erase_test() {
         }
     });
 
-    assert!(succ_inserts.lock().map(|n| *n == NUM_THREADS * NUM_OPERATIONS_PER_THREAD).unwrap());
-    assert!(succ_erases.lock().map(|n| *n == NUM_THREADS * NUM_OPERATIONS_PER_THREAD).unwrap());
+    assert!(succ_inserts
+        .lock()
+        .map(|n| *n == NUM_THREADS * NUM_OPERATIONS_PER_THREAD)
+        .unwrap());
+    assert!(succ_erases
+        .lock()
+        .map(|n| *n == NUM_THREADS * NUM_OPERATIONS_PER_THREAD)
+        .unwrap());
 
     for i in 100..(100 + NUM_THREADS * NUM_OPERATIONS_PER_THREAD) {
-        assert!(list.contains(i));
+        assert!(list.contains(&i));
     }
 
     for i in 0..(NUM_THREADS * NUM_OPERATIONS_PER_THREAD) {
-        assert!(!list.contains(i));
+        assert!(!list.contains(&i));
     }
 }
 
@@ -304,10 +320,7 @@ fn concurrent_read_test() {
 
     const TOTAL_NUM_ELEMENTS: usize = NUM_THREADS * 100000;
 
-    let list = Arc::new(SkipList::<i32>::new());
-    for i in 0..TOTAL_NUM_ELEMENTS {
-        list.insert(i as i32);
-    }
+    let list = Arc::new(SkipList::<i32>::from_sorted_iter(0..TOTAL_NUM_ELEMENTS as i32).unwrap());
     let barrier = Arc::new(Barrier::new(NUM_THREADS));
 
     scope(|s| {
@@ -323,3 +336,1378 @@ fn concurrent_read_test() {
         }
     });
 }
+
+#[test]
+fn iter_test() {
+    let list = SkipList::<i32>::new();
+
+    assert_eq!(list.iter().next(), None);
+    assert_eq!(list.iter().next_back(), None);
+
+    let keys = vec![
+        12, 16, 2, 6, 15, 8, 13, 1, 11, 14, 0, 4, 19, 10, 9, 5, 7, 3, 17, 18,
+    ];
+    for key in &keys {
+        list.insert(*key);
+    }
+
+    let expected: Vec<i32> = (0..20).collect();
+    assert_eq!(list.iter().len(), 20);
+    assert_eq!(list.iter().collect::<Vec<_>>(), expected);
+    assert_eq!((&list).into_iter().collect::<Vec<_>>(), expected);
+
+    let reversed: Vec<i32> = (0..20).rev().collect();
+    assert_eq!(list.iter().rev().collect::<Vec<_>>(), reversed);
+
+    for key in (0..20).step_by(3) {
+        list.erase(&key);
+    }
+    let expected: Vec<i32> = (0..20).filter(|k| k % 3 != 0).collect();
+    assert_eq!(list.iter().collect::<Vec<_>>(), expected);
+    assert_eq!(
+        list.iter().rev().collect::<Vec<_>>(),
+        expected.iter().rev().copied().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn iter_both_ends_test() {
+    let list = SkipList::<i32>::new();
+    for i in 0..5 {
+        list.insert(i);
+    }
+
+    let mut iter = list.iter();
+    assert_eq!(iter.next(), Some(0));
+    assert_eq!(iter.next_back(), Some(4));
+    assert_eq!(iter.next(), Some(1));
+    assert_eq!(iter.next_back(), Some(3));
+    assert_eq!(iter.len(), 1);
+    assert_eq!(iter.next_back(), Some(2));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+}
+
+#[test]
+fn iter_blocks_writers_test() {
+    let list = Arc::new(SkipList::<i32>::new());
+    for i in 0..100 {
+        list.insert(i * 2);
+    }
+
+    scope(|s| {
+        let mut iter = list.iter();
+        let first = iter.next();
+
+        let writer = {
+            let list = Arc::clone(&list);
+            s.spawn(move || {
+                for i in 0..100 {
+                    list.insert(i * 2 + 1);
+                }
+            })
+        };
+
+        // The writer cannot run until the iterator is dropped
+        let rest: Vec<i32> = iter.collect();
+        assert_eq!(first, Some(0));
+        assert_eq!(rest, (1..100).map(|i| i * 2).collect::<Vec<_>>());
+
+        writer.join().unwrap();
+    });
+
+    assert_eq!(
+        list.iter().collect::<Vec<_>>(),
+        (0..200).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn range_test() {
+    use std::collections::BTreeSet;
+    use std::ops::Bound::{self, Excluded, Included, Unbounded};
+
+    let list = SkipList::<i32>::new();
+    let mut reference = BTreeSet::new();
+    for key in (0..100).map(|i| i * 3) {
+        list.insert(key);
+        reference.insert(key);
+    }
+
+    let bounds = |key: i32| [Included(key), Excluded(key), Unbounded];
+    for start in [-1, 0, 1, 3, 50, 51, 297, 298, 400] {
+        for end in [-1, 0, 2, 3, 50, 51, 297, 299, 400] {
+            for (lower, upper) in bounds(start)
+                .into_iter()
+                .flat_map(|l| bounds(end).map(move |u| (l, u)))
+            {
+                let invalid = match (lower, upper) {
+                    (Excluded(s), Excluded(e)) if s == e => true,
+                    (Included(s) | Excluded(s), Included(e) | Excluded(e)) => s > e,
+                    _ => false,
+                };
+                if invalid {
+                    continue;
+                }
+                let range: (Bound<i32>, Bound<i32>) = (lower, upper);
+                let expected: Vec<i32> = reference.range(range).copied().collect();
+                assert_eq!(list.range(range).collect::<Vec<_>>(), expected, "{range:?}");
+                assert_eq!(
+                    list.range(range).rev().collect::<Vec<_>>(),
+                    expected.iter().rev().copied().collect::<Vec<_>>(),
+                    "{range:?}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn range_empty_list_test() {
+    let list = SkipList::<i32>::new();
+    assert_eq!(list.range(0..10).next(), None);
+    assert_eq!(list.range::<i32, _>(..).next_back(), None);
+}
+
+#[test]
+#[should_panic(expected = "range start is greater than range end")]
+fn range_inverted_bounds_test() {
+    use std::ops::Bound::{Excluded, Included};
+
+    let list = SkipList::<i32>::new();
+    list.insert(1);
+    let _ = list.range((Included(5), Excluded(1)));
+}
+
+#[test]
+fn bound_lookup_test() {
+    use std::collections::BTreeSet;
+
+    let list = SkipList::<i32>::new();
+
+    assert_eq!(list.floor(&0), None);
+    assert_eq!(list.ceiling(&0), None);
+
+    let mut reference = BTreeSet::new();
+    for key in (0..50).map(|i| i * 2) {
+        list.insert(key);
+        reference.insert(key);
+    }
+
+    for q in -2..101 {
+        let floor = reference.range(..=q).next_back().copied();
+        let ceiling = reference.range(q..).next().copied();
+        let upper = reference.range(q + 1..).next().copied();
+
+        assert_eq!(list.floor(&q), floor, "floor({q})");
+        assert_eq!(list.ceiling(&q), ceiling, "ceiling({q})");
+        assert_eq!(list.lower_bound(&q), ceiling, "lower_bound({q})");
+        assert_eq!(list.upper_bound(&q), upper, "upper_bound({q})");
+    }
+}
+
+fn tallest_tower<const MAX_HEIGHT: usize, const SEED: u32>(
+    list: &SkipList<i32, MAX_HEIGHT, SEED>,
+) -> usize {
+    let inner = list.inner.read().unwrap();
+    let mut tallest = 1;
+    let mut cur = inner.first_node();
+    while let Some(node) = cur {
+        let node = node.read().unwrap();
+        tallest = tallest.max(node.height());
+        cur = node.next_inner(0);
+    }
+    tallest
+}
+
+#[test]
+fn first_last_test() {
+    let list = SkipList::<i32>::new();
+
+    assert_eq!(list.first(), None);
+    assert_eq!(list.last(), None);
+    assert_eq!(list.pop_first(), None);
+    assert_eq!(list.pop_last(), None);
+
+    for i in 0..100 {
+        list.insert(i);
+    }
+
+    assert_eq!(list.first(), Some(0));
+    assert_eq!(list.last(), Some(99));
+
+    for i in 0..50 {
+        assert_eq!(list.pop_first(), Some(i));
+        assert_eq!(list.pop_last(), Some(99 - i));
+        assert!(!list.contains(&i));
+        assert!(!list.contains(&(99 - i)));
+        assert_eq!(list.size(), (98 - 2 * i) as usize);
+        assert_eq!(list.inner.read().unwrap().height, tallest_tower(&list));
+    }
+
+    assert!(list.empty());
+    assert_eq!(list.first(), None);
+    assert_eq!(list.last(), None);
+    assert_eq!(list.inner.read().unwrap().height, 1);
+}
+
+#[test]
+fn pop_lowers_header_height_test() {
+    let list = SkipList::<i32>::with_generator(ScriptedHeights::new([1, 2, 4]));
+    for key in [10, 20, 30] {
+        list.insert(key);
+    }
+
+    assert_eq!(list.pop_last(), Some(30));
+    assert_eq!(
+        format!("{list}"),
+        "Height: 2 | Size: 2\n[H 2], [10 1], [20 2], NIL, "
+    );
+    assert_eq!(list.validate(), Ok(()));
+}
+
+#[test]
+fn erase_keeps_height_test() {
+    let list = SkipList::<i32>::new();
+
+    for i in 0..1000 {
+        list.insert(i);
+    }
+    assert_eq!(list.inner.read().unwrap().height, tallest_tower(&list));
+
+    for i in (0..1000).step_by(7).chain(0..1000) {
+        list.erase(&i);
+        assert_eq!(list.inner.read().unwrap().height, tallest_tower(&list));
+    }
+
+    assert!(list.empty());
+}
+
+/// Builds a list of `0..len` with every tower of height one, without going
+/// through `insert`, so that very long lists stay cheap to set up.
+fn flat_list(len: i32) -> SkipList<i32> {
+    let list = SkipList::<i32>::new();
+    {
+        let mut inner = list.inner.write().unwrap();
+        let mut last = inner.header.clone();
+        for key in 0..len {
+            let node = Arc::new(RwLock::new(Node::new(key, (), 1, 0)));
+            node.write()
+                .unwrap()
+                .set_next(0, Arc::new(RwLock::new(Node::Nil)), 1);
+            last.write().unwrap().set_next(0, node.clone(), 1);
+            last = node;
+        }
+        for level in 1..14 {
+            inner
+                .header
+                .write()
+                .unwrap()
+                .set_width(level, len as usize + 1);
+        }
+        inner.size = len as usize;
+    }
+    list
+}
+
+#[test]
+fn drop_long_list_test() {
+    const LEN: i32 = 10_000_000;
+
+    let list = flat_list(LEN);
+    assert_eq!(list.size(), LEN as usize);
+    assert_eq!(list.last(), Some(LEN - 1));
+
+    std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(move || drop(list))
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn clear_long_list_test() {
+    const LEN: i32 = 10_000_000;
+
+    let list = flat_list(LEN);
+
+    let list = std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(move || {
+            list.clear();
+            list
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+
+    assert!(list.empty());
+    assert!(list.insert(1));
+    assert_eq!(list.iter().collect::<Vec<_>>(), vec![1]);
+}
+
+fn tower_heights<const MAX_HEIGHT: usize, const SEED: u32, H: HeightGenerator>(
+    list: &SkipList<i32, MAX_HEIGHT, SEED, OrdComparator, H>,
+) -> Vec<usize> {
+    let inner = list.inner.read().unwrap();
+    let mut heights = Vec::new();
+    let mut cur = inner.first_node();
+    while let Some(node) = cur {
+        let node = node.read().unwrap();
+        heights.push(node.height());
+        cur = node.next_inner(0);
+    }
+    heights
+}
+
+#[test]
+fn config_default_test() {
+    let list = SkipList::<i32>::new();
+    let configured = SkipList::<i32>::with_config(SkipListConfig::new()).unwrap();
+    let spelled_out =
+        SkipList::<i32>::with_config(SkipListConfig::new().p(0.25).max_height(14).seed(15445))
+            .unwrap();
+
+    for key in 0..1000 {
+        list.insert(key);
+        configured.insert(key);
+        spelled_out.insert(key);
+    }
+    assert_eq!(format!("{configured}"), format!("{list}"));
+    assert_eq!(format!("{spelled_out}"), format!("{list}"));
+
+    let reseeded = SkipList::<i32>::with_config(SkipListConfig::new().seed(42)).unwrap();
+    for key in 0..1000 {
+        reseeded.insert(key);
+    }
+    assert_ne!(tower_heights(&reseeded), tower_heights(&list));
+}
+
+#[test]
+fn config_validation_test() {
+    for p in [0.0, 1.0, -0.5, 1.5, f64::NAN, f64::INFINITY] {
+        let result = SkipList::<i32>::with_config(SkipListConfig::new().p(p));
+        assert!(
+            matches!(result, Err(ConfigError::InvalidProbability(_))),
+            "Accepted p = {p}"
+        );
+    }
+
+    for max_height in [0, 15] {
+        let result = SkipList::<i32>::with_config(SkipListConfig::new().max_height(max_height));
+        assert_eq!(
+            result.err(),
+            Some(ConfigError::InvalidMaxHeight {
+                max_height,
+                capacity: 14
+            })
+        );
+    }
+    assert!(SkipList::<i32, 32>::with_config(SkipListConfig::new().max_height(32)).is_ok());
+    assert!(SkipMap::<i32, ()>::with_config(SkipListConfig::new().p(0.0)).is_err());
+}
+
+#[test]
+fn config_heights_test() {
+    let list = SkipList::<i32>::with_config(SkipListConfig::new().max_height(3)).unwrap();
+    for key in 0..10000 {
+        list.insert(key);
+    }
+    assert_eq!(tallest_tower(&list), 3);
+
+    // One p of the form 1/n and one that is not
+    for p in [0.5, 0.1] {
+        let list = SkipList::<i32>::with_config(SkipListConfig::new().p(p)).unwrap();
+        for key in 0..10000 {
+            list.insert(key);
+        }
+        let promoted = tower_heights(&list).iter().filter(|&&h| h > 1).count();
+        let ratio = promoted as f64 / 10000.0;
+        assert!(
+            (ratio - p).abs() < 0.02,
+            "p = {p} promoted {ratio} of the towers"
+        );
+    }
+}
+
+/// Always draws zero, which promotes every tower to the maximum height.
+struct ZeroRng;
+
+impl rand_core::RngCore for ZeroRng {
+    fn next_u32(&mut self) -> u32 {
+        0
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        0
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        dst.fill(0);
+    }
+}
+
+#[test]
+fn height_generator_test() {
+    let list = SkipList::<i32>::new();
+    let mt = SkipList::<i32>::with_generator(Mt19937Heights::seeded(15445));
+    for key in 0..1000 {
+        list.insert(key);
+        mt.insert(key);
+    }
+    assert_eq!(format!("{mt}"), format!("{list}"));
+
+    let scripted = SkipList::<i32>::with_generator(ScriptedHeights::new([2, 1, 4, 1]));
+    for key in [3, 1, 4, 2] {
+        scripted.insert(key);
+    }
+    assert_eq!(tower_heights(&scripted), vec![1, 1, 2, 4]);
+
+    let boxed: Box<dyn HeightGenerator + Send + Sync> = Box::new(ScriptedHeights::new([2, 1]));
+    let dynamic = SkipList::<i32>::with_generator(boxed);
+    dynamic.insert(1);
+    dynamic.insert(2);
+    assert_eq!(tower_heights(&dynamic), vec![2, 1]);
+
+    let zero = SkipList::<i32, 6>::with_generator(RngHeights::new(ZeroRng));
+    for key in 0..10 {
+        zero.insert(key);
+    }
+    assert_eq!(tower_heights(&zero), vec![6; 10]);
+
+    let thread_local =
+        SkipList::<i32>::with_generator(RngHeights::with_p(ThreadLocalRng, 0.5).unwrap());
+    for key in 0..10000 {
+        thread_local.insert(key);
+    }
+    let promoted = tower_heights(&thread_local)
+        .iter()
+        .filter(|&&h| h > 1)
+        .count();
+    assert!(
+        (4500..5500).contains(&promoted),
+        "Promoted {promoted} of 10000 towers"
+    );
+}
+
+#[test]
+fn thread_local_heights_test() {
+    let list = Arc::new(SkipList::<i32>::with_generator(ThreadLocalHeights::new(
+        ThreadLocalRng,
+    )));
+    scope(|s| {
+        for t in 0..4 {
+            let list = list.clone();
+            s.spawn(move || {
+                for key in 0..1000 {
+                    assert!(list.insert(t * 1000 + key));
+                }
+            });
+        }
+    });
+    assert_eq!(list.size(), 4000);
+    assert!(list.iter().eq(0..4000));
+}
+
+#[test]
+#[should_panic(expected = "ScriptedHeights ran out after 2 heights")]
+fn scripted_heights_exhausted_test() {
+    let list = SkipList::<i32>::with_generator(ScriptedHeights::new([1, 1]));
+    for key in 0..3 {
+        list.insert(key);
+    }
+}
+
+#[test]
+fn record_heights_test() {
+    let list = SkipList::<i32>::new();
+    assert_eq!(list.recorded_heights(), None);
+
+    list.start_recording();
+    let keys = [
+        12, 16, 2, 6, 15, 8, 13, 1, 11, 14, 0, 4, 19, 10, 9, 5, 7, 3, 17, 18,
+    ];
+    for key in keys {
+        assert!(list.insert(key));
+    }
+    // Keys already present draw no height
+    assert!(!list.insert(12));
+    let heights = list.recorded_heights().unwrap();
+    // The heights `integrity_check_test` expects, in insertion order
+    assert_eq!(
+        heights,
+        vec![2, 1, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 3, 1, 1, 2, 1, 1, 1]
+    );
+
+    // Replaying the inserts against the recorded heights
+    let replayed = SkipList::<i32>::with_generator(ScriptedHeights::new(heights.clone()));
+    for key in keys {
+        replayed.insert(key);
+    }
+    assert_eq!(format!("{replayed}"), format!("{list}"));
+
+    // Rebuilding from each key's height, in another order
+    let rebuilt = SkipList::<i32>::new();
+    let mut towers: Vec<_> = keys.into_iter().zip(heights).collect();
+    towers.sort();
+    for (key, height) in towers {
+        assert!(rebuilt.insert_with_height(key, height));
+    }
+    assert_eq!(format!("{rebuilt}"), format!("{list}"));
+
+    // Explicit heights are recorded too, and erasing draws nothing
+    list.erase(&0);
+    list.insert_with_height(0, 4);
+    assert_eq!(list.stop_recording().unwrap().len(), keys.len() + 1);
+    assert_eq!(list.recorded_heights(), None);
+    assert_eq!(tallest_tower(&list), 4);
+}
+
+#[test]
+#[should_panic(expected = "Tower height 15 is not between 1 and 14")]
+fn insert_too_tall_test() {
+    let list = SkipList::<i32>::new();
+    list.insert_with_height(1, 15);
+}
+
+#[test]
+fn validate_test() {
+    let list = SkipList::<i32>::new();
+    assert_eq!(list.validate(), Ok(()));
+
+    let mut rng = mt19937::MT19937::new_with_slice_seed(&[7]);
+    for _ in 0..5000 {
+        let key = (rand_core::RngCore::next_u32(&mut rng) % 500) as i32;
+        if key % 3 == 0 {
+            list.erase(&key);
+        } else {
+            list.insert(key);
+        }
+    }
+    assert_eq!(list.validate(), Ok(()));
+
+    list.pop_first();
+    list.pop_last();
+    assert_eq!(list.validate(), Ok(()));
+
+    list.clear();
+    assert_eq!(list.validate(), Ok(()));
+}
+
+type ScriptedList = SkipList<i32, 14, 15445, OrdComparator, ScriptedHeights>;
+
+/// Builds 0..10 with the towers 1 3 1 2 1 1 3 1 1 2.
+fn scripted_list() -> ScriptedList {
+    let list =
+        SkipList::<i32>::with_generator(ScriptedHeights::new([1, 3, 1, 2, 1, 1, 3, 1, 1, 2]));
+    for key in 0..10 {
+        list.insert(key);
+    }
+    list.validate().unwrap();
+    list
+}
+
+fn node_at(list: &ScriptedList, key: i32) -> Link<i32> {
+    list.inner.read().unwrap().find(&key).unwrap()
+}
+
+#[test]
+fn validate_violations_test() {
+    let list = scripted_list();
+    if let Node::Inner { key, .. } = &mut *node_at(&list, 4).write().unwrap() {
+        *key = 7;
+    }
+    assert_eq!(
+        list.validate(),
+        Err(ValidationError::Unsorted {
+            level: 0,
+            before: 7,
+            after: 5
+        })
+    );
+
+    let list = scripted_list();
+    list.inner.write().unwrap().size = 9;
+    assert_eq!(
+        list.validate(),
+        Err(ValidationError::SizeMismatch { size: 9, count: 10 })
+    );
+
+    let list = scripted_list();
+    list.inner.write().unwrap().height = 4;
+    assert_eq!(
+        list.validate(),
+        Err(ValidationError::HeightMismatch {
+            height: 4,
+            tallest: 3
+        })
+    );
+
+    // Level 1 jumps from 1 straight to 6
+    let list = scripted_list();
+    let six = node_at(&list, 6);
+    node_at(&list, 1).write().unwrap().set_next(1, six, 5);
+    assert_eq!(
+        list.validate(),
+        Err(ValidationError::MissingFromLevel { level: 1, key: 3 })
+    );
+
+    // Level 2 ends right after 1
+    let list = scripted_list();
+    node_at(&list, 1)
+        .write()
+        .unwrap()
+        .set_next(2, Arc::new(RwLock::new(Node::Nil)), 9);
+    assert_eq!(
+        list.validate(),
+        Err(ValidationError::PrematureNil { level: 2, key: 6 })
+    );
+
+    // 3 is linked on level 2 but only level 1 passes through it
+    let list = scripted_list();
+    let three = node_at(&list, 3);
+    list.inner
+        .read()
+        .unwrap()
+        .header
+        .write()
+        .unwrap()
+        .set_next(2, three, 4);
+    assert_eq!(
+        list.validate(),
+        Err(ValidationError::LinkedAboveTower { level: 2, key: 3 })
+    );
+
+    // A stray node on level 1 that level 0 never reaches
+    let list = scripted_list();
+    let stray = Arc::new(RwLock::new(Node::new(2, (), 2, 0)));
+    stray.write().unwrap().set_next(0, node_at(&list, 3), 1);
+    stray.write().unwrap().set_next(1, node_at(&list, 3), 1);
+    node_at(&list, 1).write().unwrap().set_next(1, stray, 1);
+    assert_eq!(
+        list.validate(),
+        Err(ValidationError::NotInLowerLevel { level: 1, key: 2 })
+    );
+
+    // Level 0 loops back to the header
+    let list = scripted_list();
+    let header = list.inner.read().unwrap().header.clone();
+    node_at(&list, 9).write().unwrap().set_next(0, header, 1);
+    assert_eq!(
+        list.validate(),
+        Err(ValidationError::HeaderMidList { level: 0 })
+    );
+    // Let the teardown stop at 9
+    node_at(&list, 9)
+        .write()
+        .unwrap()
+        .set_next(0, Arc::new(RwLock::new(Node::Nil)), 1);
+
+    // Level 1 links 1 to 3 but claims to skip three nodes
+    let list = scripted_list();
+    node_at(&list, 1).write().unwrap().set_width(1, 3);
+    assert_eq!(
+        list.validate(),
+        Err(ValidationError::WrongWidth {
+            level: 1,
+            key: Some(1),
+            width: 3,
+            span: 2
+        })
+    );
+
+    // The header's link on an empty level spans the whole list
+    let list = scripted_list();
+    list.inner
+        .read()
+        .unwrap()
+        .header
+        .write()
+        .unwrap()
+        .set_width(5, 10);
+    assert_eq!(
+        list.validate(),
+        Err(ValidationError::WrongWidth {
+            level: 5,
+            key: None,
+            width: 10,
+            span: 11
+        })
+    );
+
+    let error = ValidationError::MissingFromLevel { level: 1, key: 3 };
+    assert_eq!(error.to_string(), "level 1 skips node 3");
+}
+
+/// Panics when compared against a negative key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Fragile(i32);
+
+impl Ord for Fragile {
+    fn cmp(&self, other: &Self) -> Ordering {
+        assert!(self.0 >= 0 && other.0 >= 0, "negative key");
+        self.0.cmp(&other.0)
+    }
+}
+
+impl PartialOrd for Fragile {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[test]
+fn poisoned_list_test() {
+    let list = Arc::new(SkipList::<Fragile>::new());
+    for key in 0..100 {
+        assert_eq!(list.try_insert(Fragile(key)), Ok(true));
+    }
+    assert_eq!(list.try_insert(Fragile(5)), Ok(false));
+    assert_eq!(list.try_erase(&Fragile(5)), Ok(true));
+    assert_eq!(list.try_contains(&Fragile(5)), Ok(false));
+
+    let writer = list.clone();
+    assert!(std::thread::spawn(move || writer.insert(Fragile(-1)))
+        .join()
+        .is_err());
+
+    assert!(list.is_poisoned());
+    assert_eq!(list.try_insert(Fragile(200)), Err(SkipListError::Poisoned));
+    assert_eq!(list.try_erase(&Fragile(6)), Err(SkipListError::Poisoned));
+    assert_eq!(list.try_contains(&Fragile(6)), Err(SkipListError::Poisoned));
+
+    assert_eq!(list.recover(), Ok(()));
+    assert!(!list.is_poisoned());
+    assert_eq!(list.size(), 99);
+    assert_eq!(list.try_contains(&Fragile(6)), Ok(true));
+    assert_eq!(list.try_insert(Fragile(200)), Ok(true));
+}
+
+#[test]
+fn recover_corrupted_list_test() {
+    let list = scripted_list();
+    let writer = std::thread::scope(|s| {
+        s.spawn(|| {
+            let mut inner = list.inner.write().unwrap();
+            inner.size += 1;
+            panic!("writer died halfway");
+        })
+        .join()
+    });
+    assert!(writer.is_err());
+
+    assert_eq!(
+        list.recover(),
+        Err(ValidationError::SizeMismatch {
+            size: 11,
+            count: 10
+        })
+    );
+    // Recovering still lifts the poison, so the list can be reset
+    assert_eq!(list.try_contains(&3), Ok(true));
+    list.clear();
+    assert_eq!(list.validate(), Ok(()));
+}
+
+#[test]
+fn capacity_test() {
+    let list = SkipList::<i32>::with_config(SkipListConfig::new().capacity(3)).unwrap();
+    for key in 0..3 {
+        assert_eq!(list.try_insert(key), Ok(true));
+    }
+    // Existing keys are still reported as present
+    assert_eq!(list.try_insert(1), Ok(false));
+    assert_eq!(
+        list.try_insert(3),
+        Err(SkipListError::CapacityExceeded { capacity: 3 })
+    );
+    assert!(!list.contains(&3));
+    assert_eq!(list.size(), 3);
+
+    list.erase(&0);
+    assert_eq!(list.try_insert(3), Ok(true));
+    assert_eq!(
+        SkipListError::CapacityExceeded { capacity: 3 }.to_string(),
+        "the skiplist is full at its capacity of 3 keys"
+    );
+}
+
+#[test]
+#[should_panic(expected = "the skiplist is full at its capacity of 1 keys")]
+fn insert_over_capacity_test() {
+    let list = SkipList::<i32>::with_config(SkipListConfig::new().capacity(1)).unwrap();
+    list.insert(1);
+    list.insert(2);
+}
+
+struct CaseInsensitive;
+
+impl Comparator<String> for CaseInsensitive {
+    fn compare(&self, a: &String, b: &String) -> Ordering {
+        a.to_lowercase().cmp(&b.to_lowercase())
+    }
+}
+
+#[test]
+fn case_insensitive_comparator_test() {
+    let list = SkipList::<String>::with_comparator(CaseInsensitive);
+    for word in ["banana", "Apple", "cherry", "APPLE", "Banana"] {
+        list.insert(word.to_string());
+    }
+    // The first spelling of each word wins
+    assert_eq!(
+        list.iter().collect::<Vec<_>>(),
+        vec!["Apple", "banana", "cherry"]
+    );
+    assert!(list.contains(&"CHERRY".to_string()));
+    assert!(list.erase(&"BaNaNa".to_string()));
+    assert_eq!(
+        list.range("a".to_string().."C".to_string())
+            .collect::<Vec<_>>(),
+        vec!["Apple"]
+    );
+    assert_eq!(
+        list.lower_bound(&"b".to_string()),
+        Some("cherry".to_string())
+    );
+    assert_eq!(list.validate(), Ok(()));
+}
+
+#[test]
+fn reverse_comparator_test() {
+    let list = SkipList::<i32>::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+    for key in (0..100).rev().step_by(3) {
+        list.insert(key);
+    }
+    for key in 0..100 {
+        list.insert(key);
+    }
+    assert_eq!(
+        list.iter().collect::<Vec<_>>(),
+        (0..100).rev().collect::<Vec<_>>()
+    );
+    assert_eq!(list.first(), Some(99));
+    assert_eq!(list.last(), Some(0));
+    assert_eq!(list.floor(&50), Some(50));
+    assert_eq!(
+        list.range((Bound::Included(60), Bound::Included(55)))
+            .collect::<Vec<_>>(),
+        vec![60, 59, 58, 57, 56, 55]
+    );
+    assert_eq!(list.iter().rev().take(3).collect::<Vec<_>>(), vec![0, 1, 2]);
+
+    for key in (0..100).step_by(2) {
+        assert!(list.erase(&key));
+    }
+    assert!(!list.contains(&10));
+    assert!(list.contains(&11));
+    assert_eq!(list.size(), 50);
+    assert_eq!(list.validate(), Ok(()));
+}
+
+#[test]
+#[should_panic(expected = "range start is greater than range end in SkipList")]
+fn reverse_comparator_range_test() {
+    let list = SkipList::<i32>::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+    list.range(1..5).count();
+}
+
+#[test]
+fn collation_comparator_test() {
+    // Keys need no `Ord` of their own
+    let list = SkipList::<f64>::with_comparator(f64::total_cmp);
+    for key in [2.5, -1.0, 0.0, -0.0, 10.0, 2.5] {
+        list.insert(key);
+    }
+    assert_eq!(
+        list.iter().collect::<Vec<_>>(),
+        vec![-1.0, -0.0, 0.0, 2.5, 10.0]
+    );
+
+    // Shorter words first, then alphabetically
+    let by_length = |a: &&str, b: &&str| a.len().cmp(&b.len()).then_with(|| a.cmp(b));
+    let words = SkipList::<&str>::with_comparator(by_length);
+    for word in ["pear", "fig", "banana", "kiwi", "apple"] {
+        words.insert(word);
+    }
+    assert_eq!(
+        words.iter().collect::<Vec<_>>(),
+        vec!["fig", "kiwi", "pear", "apple", "banana"]
+    );
+    assert_eq!(words.ceiling(&"zzzz"), Some("apple"));
+}
+
+#[test]
+fn rank_select_test() {
+    let list = scripted_list();
+    for key in 0..10 {
+        assert_eq!(list.rank(&key), key as usize);
+        assert_eq!(list.select(key as usize), Some(key));
+    }
+    assert_eq!(list.rank(&-1), 0);
+    assert_eq!(list.rank(&100), 10);
+    assert_eq!(list.select(10), None);
+    assert_eq!(list.nth(3), Some(3));
+
+    assert_eq!(list.count_range::<i32, _>(..), 10);
+    assert_eq!(list.count_range(2..7), 5);
+    assert_eq!(list.count_range(2..=7), 6);
+    assert_eq!(
+        list.count_range((Bound::Excluded(2), Bound::Excluded(7))),
+        4
+    );
+    assert_eq!(list.count_range(-5..3), 3);
+    assert_eq!(list.count_range(8..), 2);
+    assert_eq!(list.count_range(4..4), 0);
+    assert_eq!(list.count_range(20..30), 0);
+}
+
+#[test]
+fn widths_maintained_test() {
+    let list = SkipList::<i32>::new();
+    let mut keys = Vec::new();
+    // Interleave inserts and erases in a scrambled order
+    for i in 0..500 {
+        let key = (i * 7919) % 1000;
+        if list.insert(key) {
+            keys.push(key);
+        }
+        if i % 3 == 0 {
+            let victim = (i * 104729) % 1000;
+            if list.erase(&victim) {
+                keys.retain(|&key| key != victim);
+            }
+        }
+    }
+    keys.sort();
+    assert_eq!(list.validate(), Ok(()));
+    for (index, &key) in keys.iter().enumerate() {
+        assert_eq!(list.rank(&key), index);
+        assert_eq!(list.select(index), Some(key));
+    }
+    let middle = keys.iter().filter(|key| (250..750).contains(*key)).count();
+    assert_eq!(list.count_range(250..750), middle);
+
+    assert_eq!(list.pop_first(), Some(keys[0]));
+    assert_eq!(list.pop_last(), keys.last().copied());
+    assert_eq!(list.select(0), Some(keys[1]));
+    assert_eq!(list.validate(), Ok(()));
+
+    list.clear();
+    assert_eq!(list.validate(), Ok(()));
+    assert_eq!(list.select(0), None);
+    list.insert(5);
+    assert_eq!(list.rank(&5), 0);
+    assert_eq!(list.select(0), Some(5));
+}
+
+#[test]
+#[should_panic(expected = "range start is greater than range end in SkipList")]
+fn count_range_reversed_test() {
+    let list = scripted_list();
+    list.count_range((Bound::Included(5), Bound::Excluded(2)));
+}
+
+#[test]
+fn from_sorted_iter_test() {
+    // Heights are drawn in the same order as inserting the keys one by one
+    let loaded = SkipList::<i32>::from_sorted_iter(0..1000).unwrap();
+    let inserted = SkipList::<i32>::new();
+    for key in 0..1000 {
+        inserted.insert(key);
+    }
+    assert_eq!(format!("{loaded}"), format!("{inserted}"));
+    assert_eq!(loaded.validate(), Ok(()));
+    assert_eq!(loaded.select(500), Some(500));
+
+    let deduplicated = SkipList::<i32>::from_sorted_iter([1, 1, 2, 3, 3, 3]).unwrap();
+    assert_eq!(deduplicated.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+
+    let reversed = SkipList::<i32>::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+    assert_eq!(reversed.extend_sorted([3, 2, 1]), Ok(3));
+    assert_eq!(
+        reversed.extend_sorted([1, 2]),
+        Err(SkipListError::Unsorted { index: 1 })
+    );
+}
+
+#[test]
+fn extend_sorted_test() {
+    let list = SkipList::<i32>::new();
+    for key in (0..100).step_by(10) {
+        list.insert(key);
+    }
+    // Interleaves with the keys already present and skips the ones it shares
+    assert_eq!(list.extend_sorted((0..100).step_by(5)), Ok(10));
+    assert_eq!(
+        list.iter().collect::<Vec<_>>(),
+        (0..100).step_by(5).collect::<Vec<_>>()
+    );
+    assert_eq!(list.extend_sorted([-10, 97, 200]), Ok(3));
+    assert_eq!(list.size(), 23);
+    assert_eq!(list.rank(&97), 21);
+    assert_eq!(list.validate(), Ok(()));
+
+    // The keys before the unsorted one stay inserted
+    assert_eq!(
+        list.extend_sorted([1, 2, 3, 2, 4]),
+        Err(SkipListError::Unsorted { index: 3 })
+    );
+    assert!(list.contains(&3));
+    assert!(!list.contains(&4));
+    assert_eq!(list.validate(), Ok(()));
+    assert_eq!(
+        SkipListError::Unsorted { index: 3 }.to_string(),
+        "sorted input is out of order at index 3"
+    );
+
+    let bounded = SkipList::<i32>::with_config(SkipListConfig::new().capacity(3)).unwrap();
+    assert_eq!(
+        bounded.extend_sorted(0..10),
+        Err(SkipListError::CapacityExceeded { capacity: 3 })
+    );
+    assert_eq!(bounded.iter().collect::<Vec<_>>(), vec![0, 1, 2]);
+}
+
+#[test]
+fn collect_extend_test() {
+    let list: SkipList<i32> = [5, 1, 3, 1].into_iter().collect();
+    assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 3, 5]);
+
+    let mut list = list;
+    list.extend([4, 2]);
+    list.extend(&[6, 0]);
+    assert_eq!(list.iter().collect::<Vec<_>>(), (0..7).collect::<Vec<_>>());
+    assert_eq!(list.validate(), Ok(()));
+
+    let empty = SkipList::<i32>::default();
+    assert!(empty.empty());
+}
+
+#[test]
+fn clone_test() {
+    let list = scripted_list();
+    let copy = list.clone();
+    assert_eq!(format!("{copy}"), format!("{list}"));
+    assert_eq!(copy.validate(), Ok(()));
+    assert_eq!(copy.select(4), Some(4));
+
+    // The copy shares nothing with the original
+    copy.erase(&3);
+    list.insert_with_height(20, 1);
+    assert!(list.contains(&3));
+    assert!(!copy.contains(&20));
+
+    // The copy draws the heights the original would have
+    let list: SkipList<i32> = (0..10).collect();
+    let copy = list.clone();
+    for key in 10..100 {
+        list.insert(key);
+        copy.insert(key);
+    }
+    assert_eq!(format!("{copy}"), format!("{list}"));
+
+    // Erasing the tallest tower lowers the original before it is copied
+    let list = SkipList::<i32>::with_generator(ScriptedHeights::new([1, 4, 2]));
+    for key in [10, 20, 30] {
+        list.insert(key);
+    }
+    list.erase(&20);
+    let copy = list.clone();
+    assert_eq!(format!("{copy}"), format!("{list}"));
+    assert_eq!(copy.validate(), Ok(()));
+
+    let bounded = SkipList::<i32>::with_config(SkipListConfig::new().capacity(2)).unwrap();
+    bounded.insert(1);
+    let copy = bounded.clone();
+    assert_eq!(copy.try_insert(2), Ok(true));
+    assert_eq!(
+        copy.try_insert(3),
+        Err(SkipListError::CapacityExceeded { capacity: 2 })
+    );
+}
+
+#[test]
+fn eq_hash_test() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::BTreeSet;
+    use std::hash::{Hash, Hasher};
+
+    fn hash_of(value: &impl Hash) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // Different insertion orders give different towers but equal lists
+    let ascending: SkipList<i32> = (0..50).collect();
+    let descending: SkipList<i32> = (0..50).rev().collect();
+    assert_ne!(format!("{ascending}"), format!("{descending}"));
+    assert_eq!(ascending, descending);
+    assert_eq!(ascending, ascending);
+    assert_eq!(hash_of(&ascending), hash_of(&descending));
+    assert_eq!(
+        hash_of(&ascending),
+        hash_of(&(0..50).collect::<BTreeSet<i32>>())
+    );
+
+    descending.erase(&49);
+    assert_ne!(ascending, descending);
+    descending.insert(50);
+    assert_ne!(ascending, descending);
+    assert_ne!(hash_of(&ascending), hash_of(&descending));
+}
+
+#[test]
+fn concurrent_eq_test() {
+    let a: SkipList<i32> = (0..100).collect();
+    let b: SkipList<i32> = (0..100).collect();
+    // Comparing in both directions while writers queue up on both lists
+    // must not deadlock
+    std::thread::scope(|s| {
+        s.spawn(|| (0..1000).for_each(|_| _ = std::hint::black_box(a == b)));
+        s.spawn(|| (0..1000).for_each(|_| _ = std::hint::black_box(b == a)));
+        s.spawn(|| {
+            for i in 0..1000 {
+                a.insert(100 + i);
+                b.insert(100 + i);
+            }
+        });
+    });
+    assert_eq!(a, b);
+}
+
+#[test]
+fn into_iter_test() {
+    let list = scripted_list();
+    assert_eq!(
+        list.into_iter().collect::<Vec<_>>(),
+        (0..10).collect::<Vec<_>>()
+    );
+
+    let words: SkipList<String> = ["b", "c", "a"].into_iter().map(String::from).collect();
+    let mut keys = words.into_iter();
+    assert_eq!(keys.len(), 3);
+    assert_eq!(keys.next_back(), Some("c".to_string()));
+    assert_eq!(keys.next(), Some("a".to_string()));
+    assert_eq!(keys.collect::<Vec<_>>(), vec!["b"]);
+
+    let long = flat_list(100_000);
+    assert_eq!(long.into_iter().len(), 100_000);
+}
+
+#[test]
+fn borrowed_str_lookup_test() {
+    let list: SkipList<String> = ["pear", "apple", "fig", "kiwi"]
+        .map(String::from)
+        .into_iter()
+        .collect();
+    // Unsized ranges need explicit bounds, `Range<&str>` only covers `&str`
+    let (b, l) = (Bound::Included("b"), Bound::Excluded("l"));
+    assert!(list.contains("fig"));
+    assert!(!list.contains("grape"));
+    assert_eq!(list.floor("grape"), Some("fig".to_string()));
+    assert_eq!(list.ceiling("grape"), Some("kiwi".to_string()));
+    assert_eq!(list.lower_bound("kiwi"), Some("kiwi".to_string()));
+    assert_eq!(list.upper_bound("kiwi"), Some("pear".to_string()));
+    assert_eq!(list.rank("kiwi"), 2);
+    assert_eq!(
+        list.range::<str, _>((b, l)).collect::<Vec<_>>(),
+        vec!["fig", "kiwi"]
+    );
+    assert_eq!(
+        list.count_range::<str, _>((Bound::Included("fig"), Bound::Unbounded)),
+        3
+    );
+
+    assert!(list.erase("apple"));
+    assert!(!list.erase("apple"));
+    assert_eq!(list.try_contains("pear"), Ok(true));
+    assert_eq!(list.validate(), Ok(()));
+}
+
+#[test]
+fn borrowed_slice_lookup_test() {
+    let list = SkipList::<Vec<u8>>::new();
+    for key in [
+        b"beta".to_vec(),
+        b"alpha".to_vec(),
+        b"gamma".to_vec(),
+        vec![],
+    ] {
+        list.insert(key);
+    }
+    let key: &[u8] = b"beta";
+    assert!(list.contains(key));
+    assert!(list.contains(&[][..]));
+    assert!(!list.contains(&b"delta"[..]));
+    assert_eq!(list.floor(&b"delta"[..]), Some(b"beta".to_vec()));
+    assert_eq!(
+        list.range::<[u8], _>((Bound::Included(&b"b"[..]), Bound::Unbounded))
+            .collect::<Vec<_>>(),
+        vec![b"beta".to_vec(), b"gamma".to_vec()]
+    );
+
+    assert!(list.erase(key));
+    assert!(!list.contains(key));
+    assert_eq!(list.size(), 3);
+    assert_eq!(list.validate(), Ok(()));
+}
+
+/// A key that deliberately does not implement `Debug`.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Opaque(u32);
+
+#[test]
+fn non_debug_key_test() {
+    let list = SkipList::<Opaque>::new();
+    for key in [3, 1, 2] {
+        assert!(list.insert(Opaque(key)));
+    }
+    assert!(!list.insert(Opaque(2)));
+    assert!(list.contains(&Opaque(1)));
+    assert!(list.erase(&Opaque(1)));
+    assert!(list.floor(&Opaque(5)) == Some(Opaque(3)));
+    assert!(list.range(Opaque(2)..).map(|key| key.0).eq([2, 3]));
+    assert!(list.clone() == list);
+    assert!(list.validate().is_ok());
+
+    let mut reversed = SkipList::<Opaque>::with_comparator(|a: &Opaque, b: &Opaque| b.cmp(a));
+    reversed.extend([Opaque(1), Opaque(2)]);
+    assert!(reversed.into_iter().map(|key| key.0).eq([2, 1]));
+
+    let map = SkipMap::<Opaque, &str>::new();
+    map.insert(Opaque(1), "one");
+    assert_eq!(map.get(&Opaque(1)), Some("one"));
+
+    let set = SkipMultiSet::<Opaque>::new();
+    set.insert(Opaque(1));
+    set.insert(Opaque(1));
+    assert_eq!(set.count(&Opaque(1)), 2);
+
+    let arena = ArenaSkipList::<Opaque>::new();
+    let lazy = LazySkipList::<Opaque>::new();
+    let lock_free = LockFreeSkipList::<Opaque>::new();
+    assert!(arena.insert(Opaque(1)) && arena.contains(&Opaque(1)));
+    assert!(lazy.insert(Opaque(1)) && lazy.contains(&Opaque(1)));
+    assert!(lock_free.insert(Opaque(1)) && lock_free.contains(&Opaque(1)));
+}
+
+#[test]
+fn snapshot_test() {
+    let list = SkipList::<i32>::new();
+    for key in 0..200 {
+        list.insert(key * 2);
+    }
+    let snapshot = list.snapshot();
+    for key in 0..200 {
+        list.insert(key * 2 + 1);
+        if key % 3 == 0 {
+            list.erase(&(key * 2));
+        }
+    }
+    // Erased and inserted again after the snapshot was taken
+    list.insert(0);
+
+    let expected = (0..200).map(|key| key * 2).collect::<Vec<_>>();
+    assert_eq!(snapshot.iter().collect::<Vec<_>>(), expected);
+    assert_eq!(
+        snapshot.range(100..=140).collect::<Vec<_>>(),
+        (50..=70).map(|key| key * 2).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        snapshot
+            .range((Bound::Excluded(395), Bound::Unbounded))
+            .collect::<Vec<_>>(),
+        vec![396, 398]
+    );
+    assert!(snapshot.contains(&0));
+    assert!(snapshot.contains(&6));
+    assert!(!snapshot.contains(&1));
+
+    assert_eq!(list.size(), 200 + 200 - 67 + 1);
+    assert!(!list.contains(&6));
+    assert_eq!(list.validate(), Ok(()));
+}
+
+#[test]
+fn snapshot_versions_test() {
+    let list = SkipList::<i32>::new();
+    list.insert(1);
+    let first = list.snapshot();
+    list.insert(2);
+    let second = list.snapshot();
+    list.erase(&1);
+    list.insert(3);
+    let third = list.snapshot();
+    list.clear();
+    list.insert(4);
+
+    assert_eq!(first.iter().collect::<Vec<_>>(), vec![1]);
+    assert_eq!(second.iter().collect::<Vec<_>>(), vec![1, 2]);
+    assert_eq!(third.iter().collect::<Vec<_>>(), vec![2, 3]);
+    assert_eq!(list.iter().collect::<Vec<_>>(), vec![4]);
+    assert_eq!(list.pop_first(), Some(4));
+    assert_eq!(list.snapshot().iter().count(), 0);
+}
+
+#[test]
+fn snapshot_drop_test() {
+    let list = SkipList::<Arc<i32>>::new();
+    let key = Arc::new(1);
+    list.insert(key.clone());
+    let snapshot = list.snapshot();
+    assert!(list.erase(&key));
+    // The snapshot keeps a copy of the erased key
+    assert_eq!(Arc::strong_count(&key), 2);
+    assert!(snapshot.contains(&key));
+
+    drop(snapshot);
+    assert_eq!(Arc::strong_count(&key), 1);
+    assert_eq!(Arc::strong_count(&list.inner), 1);
+}
+
+#[test]
+fn snapshot_drop_under_iter_test() {
+    let list = SkipList::<Arc<i32>>::new();
+    let key = Arc::new(1);
+    list.insert(key.clone());
+    let snapshot = list.snapshot();
+    assert!(list.erase(&key));
+
+    // Dropping the snapshot must not wait for the iterator's read lock
+    let iter = list.iter();
+    drop(snapshot);
+    assert_eq!(Arc::strong_count(&key), 2);
+    drop(iter);
+
+    // The next write forgets the copy only the snapshot saw
+    list.insert(Arc::new(2));
+    assert_eq!(Arc::strong_count(&key), 1);
+}
+
+#[test]
+fn concurrent_snapshot_test() {
+    const NUM_KEYS: i32 = 10_000;
+
+    let list = SkipList::<i32>::from_sorted_iter((0..NUM_KEYS).map(|key| key * 2)).unwrap();
+    let snapshot = list.snapshot();
+    let expected = (0..NUM_KEYS).map(|key| key * 2).collect::<Vec<_>>();
+    scope(|s| {
+        s.spawn(|| {
+            for key in 0..NUM_KEYS {
+                list.insert(key * 2 + 1);
+                list.erase(&(key * 2));
+            }
+        });
+        for _ in 0..4 {
+            s.spawn(|| {
+                assert!(snapshot.iter().eq(expected.iter().copied()));
+                assert!(snapshot.contains(&(NUM_KEYS - 2)));
+            });
+        }
+    });
+    assert!(snapshot.iter().eq(expected.iter().copied()));
+    assert!(list.iter().eq((0..NUM_KEYS).map(|key| key * 2 + 1)));
+}
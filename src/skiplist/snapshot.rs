@@ -0,0 +1,392 @@
+use std::{
+    borrow::Borrow,
+    collections::{BTreeSet, VecDeque},
+    iter::FusedIterator,
+    ops::{Bound, RangeBounds},
+    sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, TryLockError},
+};
+
+use super::{
+    checked_bounds, Comparator, HeightGenerator, Link, Mt19937Heights, OrdComparator, SkipListInner,
+};
+
+/// How many keys a snapshot iterator copies out per read lock.
+const BATCH: usize = 64;
+
+/// The versions handed out to snapshots, and the erased keys they still see.
+///
+/// Every node records the version it was linked in, and a snapshot sees the
+/// nodes linked up to its own version. Taking a snapshot starts a new
+/// version, so keys inserted afterwards stay hidden from it. Erasing a node
+/// that a live snapshot sees moves a copy of its key to `retired`, where it
+/// stays until the last such snapshot is dropped.
+pub(crate) struct Versions<K> {
+    current: u64,
+    live: Arc<Mutex<Live>>,
+    /// Erased keys that some live snapshot still sees, sorted by key.
+    retired: Vec<Retired<K>>,
+    /// Copies keys into `retired`. Set by the first snapshot, since only
+    /// [`SkipList::snapshot`](crate::SkipList::snapshot) knows `K: Clone`.
+    clone_key: Option<fn(&K) -> K>,
+}
+
+/// The versions of the live snapshots. They sit behind their own lock so
+/// that dropping a snapshot never waits for the list's.
+#[derive(Default)]
+struct Live {
+    versions: BTreeSet<u64>,
+    /// Set when a snapshot is dropped, until the retired keys only it saw
+    /// are forgotten.
+    stale: bool,
+}
+
+fn lock(live: &Mutex<Live>) -> MutexGuard<'_, Live> {
+    live.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// An erased key, visible to the snapshots from `born` up to before `died`.
+struct Retired<K> {
+    key: K,
+    born: u64,
+    died: u64,
+}
+
+impl<K> Retired<K> {
+    fn is_visible(&self, version: u64) -> bool {
+        (self.born..self.died).contains(&version)
+    }
+}
+
+impl<K> Versions<K> {
+    pub fn new() -> Self {
+        Versions {
+            current: 0,
+            live: Arc::default(),
+            retired: Vec::new(),
+            clone_key: None,
+        }
+    }
+
+    /// Returns the version nodes linked now are born in.
+    pub fn current(&self) -> u64 {
+        self.current
+    }
+
+    /// Returns `true` if any snapshot is alive.
+    pub fn any_live(&self) -> bool {
+        !lock(&self.live).versions.is_empty()
+    }
+
+    /// Registers a new snapshot and returns its version.
+    fn open(&mut self, clone_key: fn(&K) -> K) -> u64 {
+        self.prune();
+        let version = self.current;
+        self.current += 1;
+        lock(&self.live).versions.insert(version);
+        self.clone_key = Some(clone_key);
+        version
+    }
+
+    /// Forgets the erased keys no live snapshot sees anymore.
+    pub fn prune(&mut self) {
+        let mut live = lock(&self.live);
+        if !live.stale {
+            return;
+        }
+        live.stale = false;
+        self.retired
+            .retain(|old| live.versions.range(old.born..old.died).next().is_some());
+    }
+
+    /// Keeps a copy of `key`, erased from a node born in `born`, if a live
+    /// snapshot sees it.
+    pub fn retire(&mut self, cmp: &impl Comparator<K>, key: &K, born: u64) {
+        self.prune();
+        if lock(&self.live).versions.range(born..).next().is_none() {
+            return;
+        }
+        let clone_key = self
+            .clone_key
+            .expect("Snapshots register how to copy keys when taken");
+        let index = self
+            .retired
+            .partition_point(|old| cmp.compare(&old.key, key).is_le());
+        self.retired.insert(
+            index,
+            Retired {
+                key: clone_key(key),
+                born,
+                died: self.current,
+            },
+        );
+    }
+
+    /// Returns the retired keys that satisfy `start`, in order.
+    fn retired_from<Q>(
+        &self,
+        cmp: &impl Comparator<Q>,
+        start: Bound<&Q>,
+    ) -> impl Iterator<Item = &Retired<K>>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized,
+    {
+        let skipped = self.retired.partition_point(|old| match start {
+            Bound::Included(start) => cmp.compare(old.key.borrow(), start).is_lt(),
+            Bound::Excluded(start) => cmp.compare(old.key.borrow(), start).is_le(),
+            Bound::Unbounded => false,
+        });
+        self.retired[skipped..].iter()
+    }
+}
+
+/// A point-in-time view of a [`SkipList`](crate::SkipList), taken with
+/// [`SkipList::snapshot`](crate::SkipList::snapshot).
+///
+/// The snapshot sees exactly the keys the list held when it was taken,
+/// whatever is inserted or erased afterwards. It holds no lock between
+/// calls: its iterators read-lock the list for one batch of keys at a time,
+/// so writers only ever wait for a batch instead of a whole scan. Keys
+/// erased after the snapshot was taken are copied aside and kept until the
+/// last snapshot that sees them is dropped. Dropping a snapshot never takes
+/// the list's write lock, so it is safe while the same thread holds an
+/// [`Iter`](crate::Iter) or [`Range`](crate::Range); the copies it alone
+/// kept are then freed by the next write instead.
+///
+/// ```
+/// use p0::SkipList;
+///
+/// let list = SkipList::<i32>::new();
+/// for key in [1, 2, 3] {
+///     list.insert(key);
+/// }
+/// let snapshot = list.snapshot();
+/// list.erase(&2);
+/// list.insert(4);
+///
+/// assert_eq!(snapshot.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+/// assert_eq!(snapshot.range(2..).collect::<Vec<_>>(), vec![2, 3]);
+/// assert!(snapshot.contains(&2));
+/// assert!(!snapshot.contains(&4));
+/// assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 3, 4]);
+/// ```
+pub struct Snapshot<
+    K,
+    const MAX_HEIGHT: usize = 14,
+    const SEED: u32 = 15445,
+    C = OrdComparator,
+    H = Mt19937Heights,
+> {
+    inner: Arc<RwLock<SkipListInner<K, (), MAX_HEIGHT, SEED, C, H>>>,
+    live: Arc<Mutex<Live>>,
+    version: u64,
+}
+
+impl<K: Clone, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator>
+    Snapshot<K, MAX_HEIGHT, SEED, C, H>
+{
+    pub(crate) fn new(inner: Arc<RwLock<SkipListInner<K, (), MAX_HEIGHT, SEED, C, H>>>) -> Self {
+        let mut inner_write_lock = inner.write().unwrap();
+        let version = inner_write_lock.versions.open(K::clone);
+        let live = inner_write_lock.versions.live.clone();
+        drop(inner_write_lock);
+        Snapshot {
+            inner,
+            live,
+            version,
+        }
+    }
+
+    /// Returns `true` if `key` was in the list when the snapshot was taken.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
+        let inner = self.inner.read().unwrap();
+        if let Some(node) = inner.find(key) {
+            if node.read().unwrap().born() <= self.version {
+                return true;
+            }
+        }
+        // A key erased and inserted again since has a newer node in the list
+        // and its old copy among the retired keys
+        let retired = inner
+            .versions
+            .retired_from(&inner.cmp, Bound::Included(key))
+            .take_while(|old| inner.cmp.compare(old.key.borrow(), key).is_eq())
+            .any(|old| old.is_visible(self.version));
+        retired
+    }
+
+    /// Returns an iterator over the snapshot's keys in ascending order.
+    pub fn iter(&self) -> SnapshotIter<'_, K, MAX_HEIGHT, SEED, C, H> {
+        self.range::<K, _>(..)
+    }
+
+    /// Returns an iterator over the snapshot's keys inside `range`, in
+    /// ascending order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if both
+    /// ends are equal and excluded, like [`SkipList::range`](crate::SkipList::range).
+    pub fn range<'a, Q, R>(&'a self, range: R) -> SnapshotIter<'a, K, MAX_HEIGHT, SEED, C, H>
+    where
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized + 'a,
+        R: RangeBounds<Q> + 'a,
+    {
+        let mut batch = VecDeque::new();
+        let done = {
+            let inner = self.inner.read().unwrap();
+            let (start, end) = checked_bounds(&range, &inner.cmp, "Snapshot");
+            drop(inner);
+            self.fill(
+                start,
+                |cmp, key| is_past(cmp, key.borrow(), end),
+                &mut batch,
+            )
+        };
+        SnapshotIter {
+            snapshot: self,
+            after: batch.back().cloned(),
+            batch,
+            done,
+            past_end: Box::new(move |cmp, key| is_past(cmp, key.borrow(), range.end_bound())),
+        }
+    }
+
+    /// Copies the next keys the snapshot sees, starting at `start`, into
+    /// `batch`, under a single read lock. Returns `true` once there are no
+    /// keys left before `past_end`.
+    fn fill<Q>(
+        &self,
+        start: Bound<&Q>,
+        past_end: impl Fn(&C, &K) -> bool,
+        batch: &mut VecDeque<K>,
+    ) -> bool
+    where
+        K: Borrow<Q>,
+        C: Comparator<Q>,
+        Q: ?Sized,
+    {
+        let inner = self.inner.read().unwrap();
+        let mut node = inner.lower_node(start);
+        let mut retired = inner
+            .versions
+            .retired_from(&inner.cmp, start)
+            .filter(|old| old.is_visible(self.version))
+            .peekable();
+        while batch.len() < BATCH {
+            // Skip the nodes linked after the snapshot was taken
+            let is_newer = |node: &mut Link<K>| node.read().unwrap().born() > self.version;
+            while let Some(newer) = node.take_if(is_newer) {
+                node = newer.read().unwrap().next_inner(0);
+            }
+            let from_retired;
+            let key = {
+                let node_read_lock = node.as_ref().map(|node| node.read().unwrap());
+                let live_key = node_read_lock.as_ref().and_then(|node| node.key());
+                from_retired = match (live_key, retired.peek()) {
+                    (Some(live_key), Some(old)) => inner.cmp.compare(&old.key, live_key).is_lt(),
+                    (None, old) => old.is_some(),
+                    (Some(_), None) => false,
+                };
+                if from_retired {
+                    retired.peek().map(|old| old.key.clone())
+                } else {
+                    live_key.cloned()
+                }
+            };
+            let Some(key) = key.filter(|key| !past_end(&inner.cmp, key)) else {
+                return true;
+            };
+            if from_retired {
+                retired.next();
+            } else if let Some(cur) = node.take() {
+                node = cur.read().unwrap().next_inner(0);
+            }
+            batch.push_back(key);
+        }
+        false
+    }
+}
+
+fn is_past<Q: ?Sized>(cmp: &impl Comparator<Q>, key: &Q, end: Bound<&Q>) -> bool {
+    match end {
+        Bound::Included(end) => cmp.compare(key, end).is_gt(),
+        Bound::Excluded(end) => cmp.compare(key, end).is_ge(),
+        Bound::Unbounded => false,
+    }
+}
+
+impl<K, const MAX_HEIGHT: usize, const SEED: u32, C, H> Drop
+    for Snapshot<K, MAX_HEIGHT, SEED, C, H>
+{
+    fn drop(&mut self) {
+        let mut live = lock(&self.live);
+        live.versions.remove(&self.version);
+        live.stale = true;
+        drop(live);
+        // Blocking here would deadlock if this thread holds a read lock
+        let inner = match self.inner.try_write() {
+            Ok(inner) => Some(inner),
+            Err(TryLockError::Poisoned(error)) => Some(error.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        };
+        if let Some(mut inner) = inner {
+            inner.versions.prune();
+        }
+    }
+}
+
+/// An iterator over the keys of a [`Snapshot`], in ascending order.
+///
+/// Keys are copied out in batches, each under its own short read lock, so
+/// the iterator never blocks writers for long and does not deadlock with
+/// writes on the same thread.
+pub struct SnapshotIter<
+    'a,
+    K,
+    const MAX_HEIGHT: usize = 14,
+    const SEED: u32 = 15445,
+    C = OrdComparator,
+    H = Mt19937Heights,
+> {
+    snapshot: &'a Snapshot<K, MAX_HEIGHT, SEED, C, H>,
+    /// Keys copied out but not yielded yet.
+    batch: VecDeque<K>,
+    /// The last key copied out, where the next batch starts after.
+    after: Option<K>,
+    /// Whether the last key of the range has been copied out.
+    done: bool,
+    past_end: PastEnd<'a, K, C>,
+}
+
+/// Tells whether a key lies after the end of an iterator's range.
+type PastEnd<'a, K, C> = Box<dyn Fn(&C, &K) -> bool + 'a>;
+
+impl<K: Clone, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator>
+    Iterator for SnapshotIter<'_, K, MAX_HEIGHT, SEED, C, H>
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        if self.batch.is_empty() && !self.done {
+            let after = self.after.take()?;
+            self.done =
+                self.snapshot
+                    .fill(Bound::Excluded(&after), &self.past_end, &mut self.batch);
+            self.after = self.batch.back().cloned();
+        }
+        self.batch.pop_front()
+    }
+}
+
+impl<K: Clone, const MAX_HEIGHT: usize, const SEED: u32, C: Comparator<K>, H: HeightGenerator>
+    FusedIterator for SnapshotIter<'_, K, MAX_HEIGHT, SEED, C, H>
+{
+}
@@ -0,0 +1,314 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt::{Debug, Display},
+    sync::{Arc, RwLock},
+};
+
+use super::{Comparator, HeightGenerator, Link, Node, SkipListInner};
+
+/// The first broken invariant found by [`SkipList::validate`](crate::SkipList::validate).
+///
+/// Levels are numbered from 0 at the bottom, and keys name the nodes
+/// involved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationError<K> {
+    /// `after` follows `before` on `level` but is not greater, or is smaller
+    /// in a [`SkipMultiSet`](crate::SkipMultiSet).
+    #[non_exhaustive]
+    Unsorted { level: usize, before: K, after: K },
+    /// The node's recorded height disagrees with its number of links, or it
+    /// has no links at all.
+    #[non_exhaustive]
+    MalformedTower { key: K, height: usize, links: usize },
+    /// The node is reached on `level` but its tower is not that tall.
+    #[non_exhaustive]
+    LinkedAboveTower { level: usize, key: K },
+    /// The node is linked on `level` but not on the level below, so `level`
+    /// is not a subsequence of it.
+    #[non_exhaustive]
+    NotInLowerLevel { level: usize, key: K },
+    /// The node's tower reaches `level`, but the level skips over it.
+    #[non_exhaustive]
+    MissingFromLevel { level: usize, key: K },
+    /// `level` ends in `Nil` before reaching the node, whose tower reaches
+    /// that level.
+    #[non_exhaustive]
+    PrematureNil { level: usize, key: K },
+    /// The header is reachable from inside `level`.
+    #[non_exhaustive]
+    HeaderMidList { level: usize },
+    /// The list claims `size` keys but level 0 holds `count`. Walks stop
+    /// counting at `size + 1`, so a level that never ends reports that.
+    #[non_exhaustive]
+    SizeMismatch { size: usize, count: usize },
+    /// The list claims `height` levels but its tallest tower has `tallest`.
+    #[non_exhaustive]
+    HeightMismatch { height: usize, tallest: usize },
+    /// The list claims `height` levels but its header records `header`.
+    #[non_exhaustive]
+    HeaderHeightMismatch { height: usize, header: usize },
+    /// The link leaving the node on `level`, or the header's if `key` is
+    /// `None`, records a width of `width` but spans `span` nodes of level 0.
+    #[non_exhaustive]
+    WrongWidth {
+        level: usize,
+        key: Option<K>,
+        width: usize,
+        span: usize,
+    },
+}
+
+impl<K: Debug> Display for ValidationError<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::Unsorted {
+                level,
+                before,
+                after,
+            } => f.write_fmt(format_args!(
+                "{after:?} follows {before:?} on level {level}"
+            )),
+            ValidationError::MalformedTower { key, height, links } => f.write_fmt(format_args!(
+                "node {key:?} has height {height} but {links} links"
+            )),
+            ValidationError::LinkedAboveTower { level, key } => f.write_fmt(format_args!(
+                "node {key:?} is linked on level {level}, above its tower"
+            )),
+            ValidationError::NotInLowerLevel { level, key } => f.write_fmt(format_args!(
+                "node {key:?} is linked on level {level} but not on level {}",
+                level - 1
+            )),
+            ValidationError::MissingFromLevel { level, key } => {
+                f.write_fmt(format_args!("level {level} skips node {key:?}"))
+            }
+            ValidationError::PrematureNil { level, key } => {
+                f.write_fmt(format_args!("level {level} ends before node {key:?}"))
+            }
+            ValidationError::HeaderMidList { level } => {
+                f.write_fmt(format_args!("level {level} links back to the header"))
+            }
+            ValidationError::SizeMismatch { size, count } => f.write_fmt(format_args!(
+                "size is {size} but level 0 holds {count} nodes"
+            )),
+            ValidationError::HeightMismatch { height, tallest } => f.write_fmt(format_args!(
+                "height is {height} but the tallest tower has {tallest} levels"
+            )),
+            ValidationError::HeaderHeightMismatch { height, header } => f.write_fmt(format_args!(
+                "height is {height} but the header records {header} levels"
+            )),
+            ValidationError::WrongWidth {
+                level,
+                key: Some(key),
+                width,
+                span,
+            } => f.write_fmt(format_args!(
+                "the link from node {key:?} on level {level} has width {width} but spans {span}"
+            )),
+            ValidationError::WrongWidth {
+                level,
+                key: None,
+                width,
+                span,
+            } => f.write_fmt(format_args!(
+                "the header's link on level {level} has width {width} but spans {span}"
+            )),
+        }
+    }
+}
+
+impl<K: Debug> Error for ValidationError<K> {}
+
+impl<
+        K: Clone,
+        V,
+        const MAX_HEIGHT: usize,
+        const SEED: u32,
+        C: Comparator<K>,
+        H: HeightGenerator,
+    > SkipListInner<K, V, MAX_HEIGHT, SEED, C, H>
+{
+    /// Checks every structural invariant, bottom level first, and returns
+    /// the first violation. Equal neighbours only count as unsorted unless
+    /// `allow_equal` is set.
+    pub fn validate(&self, allow_equal: bool) -> Result<(), ValidationError<K>> {
+        let mut tallest = 1;
+        let mut below = self.walk(0, allow_equal)?;
+        for node in &below {
+            let node_read_lock = node.read().unwrap();
+            let Node::Inner {
+                key, height, links, ..
+            } = &*node_read_lock
+            else {
+                unreachable!("Walks only yield inner nodes");
+            };
+            if links.is_empty() || *height != links.len() {
+                return Err(ValidationError::MalformedTower {
+                    key: key.clone(),
+                    height: *height,
+                    links: links.len(),
+                });
+            }
+            tallest = tallest.max(links.len());
+        }
+        if below.len() != self.size {
+            return Err(ValidationError::SizeMismatch {
+                size: self.size,
+                count: below.len(),
+            });
+        }
+        let positions: HashMap<*const RwLock<Node<K, V>>, usize> = below
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (Arc::as_ptr(node), index + 1))
+            .collect();
+        self.check_widths(0, &below, &positions)?;
+
+        for level in 1..MAX_HEIGHT {
+            let level_nodes = self.walk(level, allow_equal)?;
+            let below_set: HashSet<*const RwLock<Node<K, V>>> =
+                below.iter().map(Arc::as_ptr).collect();
+            // Level `level` must be exactly the nodes below that are tall
+            // enough to reach it
+            let mut expected = below
+                .iter()
+                .filter(|node| node.read().unwrap().height() > level);
+            for node in &level_nodes {
+                match expected.next() {
+                    Some(next) if Arc::ptr_eq(next, node) => {}
+                    Some(next) if below_set.contains(&Arc::as_ptr(node)) => {
+                        return Err(ValidationError::MissingFromLevel {
+                            level,
+                            key: key_of(next),
+                        });
+                    }
+                    _ => {
+                        return Err(ValidationError::NotInLowerLevel {
+                            level,
+                            key: key_of(node),
+                        });
+                    }
+                }
+            }
+            if let Some(next) = expected.next() {
+                return Err(ValidationError::PrematureNil {
+                    level,
+                    key: key_of(next),
+                });
+            }
+            self.check_widths(level, &level_nodes, &positions)?;
+            below = level_nodes;
+        }
+
+        if self.height != tallest {
+            return Err(ValidationError::HeightMismatch {
+                height: self.height,
+                tallest,
+            });
+        }
+        let header = self.header.read().unwrap().height();
+        if self.height != header {
+            return Err(ValidationError::HeaderHeightMismatch {
+                height: self.height,
+                header,
+            });
+        }
+        Ok(())
+    }
+
+    /// Collects the nodes linked on `level`, checking that they are sorted,
+    /// reach that level, and that the level ends in `Nil`.
+    fn walk(&self, level: usize, allow_equal: bool) -> Result<Vec<Link<K, V>>, ValidationError<K>> {
+        let mut nodes: Vec<Link<K, V>> = Vec::new();
+        let mut next = self.header.read().unwrap().next(level);
+        while let Some(node) = next {
+            if nodes.len() > self.size {
+                return Err(ValidationError::SizeMismatch {
+                    size: self.size,
+                    count: nodes.len(),
+                });
+            }
+            let node_read_lock = node.read().unwrap();
+            let key = match &*node_read_lock {
+                Node::Nil => break,
+                Node::Header { .. } => return Err(ValidationError::HeaderMidList { level }),
+                Node::Inner { key, .. } => key,
+            };
+            // Also stops the walk if the level loops back on itself
+            if let Some(before) = nodes
+                .last()
+                .filter(|before| !self.is_before(before, key, allow_equal))
+            {
+                return Err(ValidationError::Unsorted {
+                    level,
+                    before: key_of(before),
+                    after: key.clone(),
+                });
+            }
+            next = node_read_lock.next(level);
+            if next.is_none() {
+                return Err(ValidationError::LinkedAboveTower {
+                    level,
+                    key: key.clone(),
+                });
+            }
+            drop(node_read_lock);
+            nodes.push(node);
+        }
+        Ok(nodes)
+    }
+
+    /// Checks that every link on `level`, from the header through `nodes` to
+    /// the closing `Nil`, spans as many level-0 steps as its width says.
+    /// `positions` maps each node to its place on level 0, the header being 0.
+    fn check_widths(
+        &self,
+        level: usize,
+        nodes: &[Link<K, V>],
+        positions: &HashMap<*const RwLock<Node<K, V>>, usize>,
+    ) -> Result<(), ValidationError<K>> {
+        let check = |from: &Link<K, V>, span: usize| {
+            let from_read_lock = from.read().unwrap();
+            let width = from_read_lock.width(level);
+            if width == span {
+                return Ok(());
+            }
+            Err(ValidationError::WrongWidth {
+                level,
+                key: from_read_lock.key().cloned(),
+                width,
+                span,
+            })
+        };
+        let mut from = self.header.clone();
+        let mut from_position = 0;
+        for node in nodes {
+            let position = positions[&Arc::as_ptr(node)];
+            check(&from, position - from_position)?;
+            from = node.clone();
+            from_position = position;
+        }
+        // The closing `Nil` sits one past the last node
+        check(&from, self.size + 1 - from_position)
+    }
+
+    fn is_before(&self, node: &Link<K, V>, key: &K, allow_equal: bool) -> bool {
+        match &*node.read().unwrap() {
+            Node::Inner { key: node_key, .. } => match self.cmp.compare(node_key, key) {
+                Ordering::Less => true,
+                Ordering::Equal => allow_equal,
+                Ordering::Greater => false,
+            },
+            _ => unreachable!("Walks only yield inner nodes"),
+        }
+    }
+}
+
+fn key_of<K: Clone, V>(node: &Link<K, V>) -> K {
+    match &*node.read().unwrap() {
+        Node::Inner { key, .. } => key.clone(),
+        _ => unreachable!("Walks only yield inner nodes"),
+    }
+}
@@ -0,0 +1,200 @@
+use std::{
+    borrow::Borrow,
+    fmt::{Debug, Display},
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, RwLock},
+};
+
+use crate::skiplist::{
+    ConfigError, HeightGenerator, Mt19937Heights, OrdComparator, SkipListConfig, SkipListError,
+    SkipListInner,
+};
+
+/// A concurrent ordered map backed by a skiplist.
+///
+/// `SkipMap` shares its node layout and search paths with
+/// [`SkipList`](crate::SkipList); each node simply carries a value next to
+/// its key. Values live behind the list's locks, so lookups hand out clones
+/// or run a closure against the stored value instead of returning references.
+///
+/// ```
+/// use p0::SkipMap;
+///
+/// let map = SkipMap::<u32, String>::new();
+/// assert_eq!(map.insert(7, "seven".to_string()), None);
+/// assert_eq!(map.insert(7, "SEVEN".to_string()), Some("seven".to_string()));
+///
+/// assert_eq!(map.get(&7), Some("SEVEN".to_string()));
+/// assert_eq!(map.update(&7, |v| v.push('!')), Some(()));
+/// assert_eq!(map.get_with(&7, |v| v.len()), Some(6));
+///
+/// assert_eq!(map.remove(&7), Some("SEVEN!".to_string()));
+/// assert!(!map.contains_key(&7));
+/// ```
+pub struct SkipMap<
+    K: Ord,
+    V,
+    const MAX_HEIGHT: usize = 14,
+    const SEED: u32 = 15445,
+    H = Mt19937Heights,
+> {
+    inner: Arc<RwLock<SkipListInner<K, V, MAX_HEIGHT, SEED, OrdComparator, H>>>,
+}
+
+impl<K: Ord, V, const MAX_HEIGHT: usize, const SEED: u32> SkipMap<K, V, MAX_HEIGHT, SEED> {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        SkipMap {
+            inner: Arc::new(RwLock::new(SkipListInner::new())),
+        }
+    }
+
+    /// Creates an empty map that draws tower heights from `heights`, up to
+    /// `MAX_HEIGHT`; see [`SkipList::with_generator`](crate::SkipList::with_generator).
+    pub fn with_generator<H: HeightGenerator>(heights: H) -> SkipMap<K, V, MAX_HEIGHT, SEED, H> {
+        SkipMap {
+            inner: Arc::new(RwLock::new(SkipListInner::with_generator(
+                heights,
+                MAX_HEIGHT,
+                OrdComparator,
+            ))),
+        }
+    }
+
+    /// Creates an empty map whose tower heights follow `config`; see
+    /// [`SkipList::with_config`](crate::SkipList::with_config).
+    pub fn with_config(config: SkipListConfig) -> Result<Self, ConfigError> {
+        Ok(SkipMap {
+            inner: Arc::new(RwLock::new(SkipListInner::with_config(config)?)),
+        })
+    }
+}
+
+impl<K: Ord, V, const MAX_HEIGHT: usize, const SEED: u32, H: HeightGenerator>
+    SkipMap<K, V, MAX_HEIGHT, SEED, H>
+{
+    /// Returns `true` if the map holds no entries.
+    pub fn empty(&self) -> bool {
+        self.inner.read().unwrap().empty()
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn size(&self) -> usize {
+        self.inner.read().unwrap().size()
+    }
+
+    /// Inserts `value` under `key`, returning the value it replaced, if any.
+    ///
+    /// Replacing a value keeps the existing node and its tower in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is new and the map is at its configured
+    /// [capacity](SkipListConfig::capacity).
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        let mut inner = self.inner.write().unwrap();
+        inner.upsert(key, value)
+    }
+
+    /// Like [`insert`](Self::insert), but reports a poisoned or full map
+    /// instead of panicking; see [`SkipListError`].
+    pub fn try_insert(&self, key: K, value: V) -> Result<Option<V>, SkipListError> {
+        let mut inner = self.inner.write().map_err(|_| SkipListError::Poisoned)?;
+        inner.try_upsert(key, value)
+    }
+
+    /// Returns a clone of the value stored under `key`.
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        V: Clone,
+    {
+        self.get_with(key, V::clone)
+    }
+
+    /// Runs `f` against the value stored under `key` while holding a read
+    /// lock on the map.
+    pub fn get_with<Q, R>(&self, key: &Q, f: impl FnOnce(&V) -> R) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let inner = self.inner.read().unwrap();
+        let node = inner.find(key)?;
+        let node_read_lock = node.read().unwrap();
+        node_read_lock.value().map(f)
+    }
+
+    /// Runs `f` against a mutable reference to the value stored under `key`
+    /// while holding a read lock on the map and a write lock on the entry.
+    ///
+    /// If `f` panics, the panic is resumed once both locks are released, so
+    /// the map stays usable. The value is left as `f` left it.
+    pub fn update<Q, R>(&self, key: &Q, f: impl FnOnce(&mut V) -> R) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let inner = self.inner.read().unwrap();
+        let node = inner.find(key)?;
+        let mut node_write_lock = node.write().unwrap();
+        let value = node_write_lock.value_mut()?;
+        // A guard dropped while unwinding would poison the entry's lock
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(value)));
+        drop(node_write_lock);
+        Some(result.unwrap_or_else(|payload| panic::resume_unwind(payload)))
+    }
+
+    /// Removes `key`, returning its value if it was present.
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut inner = self.inner.write().unwrap();
+        inner.remove(key).map(|(_, value)| value)
+    }
+
+    /// Returns `true` if the map holds an entry for `key`.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.inner.read().unwrap().contains(key)
+    }
+
+    /// Removes every entry from the map.
+    pub fn clear(&self) {
+        let mut inner = self.inner.write().unwrap();
+        inner.clear();
+    }
+}
+
+impl<K: Ord, V, const MAX_HEIGHT: usize, const SEED: u32> Default
+    for SkipMap<K, V, MAX_HEIGHT, SEED>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Debug, V, const MAX_HEIGHT: usize, const SEED: u32, H> Display
+    for SkipMap<K, V, MAX_HEIGHT, SEED, H>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.inner.read().unwrap(), f)
+    }
+}
+
+impl<K: Ord + Debug, V, const MAX_HEIGHT: usize, const SEED: u32, H> Debug
+    for SkipMap<K, V, MAX_HEIGHT, SEED, H>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.inner.read().unwrap(), f)
+    }
+}
+
+#[cfg(test)]
+mod skipmap_test;
@@ -0,0 +1,186 @@
+use std::sync::Arc;
+use std::sync::Barrier;
+use std::thread::scope;
+
+use super::*;
+
+#[test]
+fn insert_get_test() {
+    let map = SkipMap::<i32, String>::new();
+
+    assert!(map.empty());
+
+    for i in 0..10 {
+        assert_eq!(map.insert(i, format!("v{i}")), None);
+    }
+
+    assert_eq!(map.size(), 10);
+
+    for i in 0..10 {
+        assert!(map.contains_key(&i));
+        assert_eq!(map.get(&i), Some(format!("v{i}")));
+    }
+
+    assert!(!map.contains_key(&10));
+    assert_eq!(map.get(&10), None);
+}
+
+#[test]
+fn borrowed_key_test() {
+    let map = SkipMap::<String, usize>::new();
+    for word in ["one", "two", "three"] {
+        map.insert(word.to_string(), word.len());
+    }
+
+    assert!(map.contains_key("two"));
+    assert_eq!(map.get("three"), Some(5));
+    assert_eq!(map.update("one", |v| *v += 10), Some(()));
+    assert_eq!(map.get_with("one", |v| *v), Some(13));
+    assert_eq!(map.remove("two"), Some(3));
+    assert_eq!(map.get("two"), None);
+}
+
+#[test]
+fn insert_replaces_value_test() {
+    let map = SkipMap::<i32, i32>::new();
+
+    for i in 0..10 {
+        assert_eq!(map.insert(i, i), None);
+    }
+
+    let before = format!("{map}");
+    for i in 0..10 {
+        assert_eq!(map.insert(i, i * 100), Some(i));
+    }
+
+    // Replacing values must not rebuild any tower
+    assert_eq!(format!("{map}"), before);
+    assert_eq!(map.size(), 10);
+
+    for i in 0..10 {
+        assert_eq!(map.get(&i), Some(i * 100));
+    }
+}
+
+#[test]
+fn try_insert_capacity_test() {
+    let map = SkipMap::<i32, i32>::with_config(SkipListConfig::new().capacity(2)).unwrap();
+    assert_eq!(map.try_insert(1, 10), Ok(None));
+    assert_eq!(map.try_insert(2, 20), Ok(None));
+    // Replacing a value needs no room
+    assert_eq!(map.try_insert(1, 11), Ok(Some(10)));
+    assert_eq!(
+        map.try_insert(3, 30),
+        Err(SkipListError::CapacityExceeded { capacity: 2 })
+    );
+    assert_eq!(map.get(&3), None);
+    assert_eq!(map.size(), 2);
+
+    map.remove(&2);
+    assert_eq!(map.try_insert(3, 30), Ok(None));
+}
+
+#[test]
+#[should_panic(expected = "capacity of 1 keys")]
+fn insert_over_capacity_test() {
+    let map = SkipMap::<i32, i32>::with_config(SkipListConfig::new().capacity(1)).unwrap();
+    map.insert(1, 10);
+    map.insert(2, 20);
+}
+
+#[test]
+fn update_test() {
+    let map = SkipMap::<i32, Vec<i32>>::new();
+
+    map.insert(1, vec![]);
+
+    assert_eq!(map.update(&1, |v| v.push(10)), Some(()));
+    assert_eq!(
+        map.update(&1, |v| {
+            v.push(20);
+            v.len()
+        }),
+        Some(2)
+    );
+    assert_eq!(map.update(&2, |v| v.push(10)), None);
+
+    assert_eq!(map.get_with(&1, |v| v.iter().sum::<i32>()), Some(30));
+    assert_eq!(map.get_with(&2, |v| v.len()), None);
+}
+
+#[test]
+fn panicking_update_test() {
+    let map = SkipMap::<i32, Vec<i32>>::new();
+    map.insert(1, vec![10]);
+    map.insert(2, vec![]);
+
+    let result = std::panic::catch_unwind(|| {
+        map.update(&1, |v| {
+            v.push(20);
+            panic!("update failed");
+        })
+    });
+    assert!(result.is_err());
+
+    // Neither the map nor the entry is poisoned
+    assert_eq!(map.get(&1), Some(vec![10, 20]));
+    assert_eq!(map.update(&1, |v| v.pop()), Some(Some(20)));
+    assert_eq!(map.insert(3, vec![30]), None);
+    assert_eq!(map.remove(&1), Some(vec![10]));
+    assert_eq!(map.size(), 2);
+}
+
+#[test]
+fn remove_test() {
+    let map = SkipMap::<i32, String>::new();
+
+    for i in 0..5 {
+        map.insert(i, i.to_string());
+    }
+
+    assert_eq!(map.remove(&10), None);
+
+    for i in 0..5 {
+        assert_eq!(map.remove(&i), Some(i.to_string()));
+        assert!(!map.contains_key(&i));
+        assert_eq!(map.size(), (5 - i - 1) as usize);
+    }
+
+    assert!(map.empty());
+
+    map.insert(1, "again".to_string());
+    map.clear();
+    assert!(map.empty());
+    assert_eq!(map.get(&1), None);
+}
+
+#[test]
+fn concurrent_insert_test() {
+    const NUM_THREADS: i32 = 10;
+    const NUM_INSERTIONS_PER_THREAD: i32 = 100;
+
+    let map = Arc::new(SkipMap::<i32, i32>::new());
+    let barrier = Arc::new(Barrier::new(NUM_THREADS as usize));
+
+    scope(|s| {
+        for i in 0..NUM_THREADS {
+            let map = Arc::clone(&map);
+            let barrier = Arc::clone(&barrier);
+            s.spawn(move || {
+                barrier.wait();
+                let start = i * NUM_INSERTIONS_PER_THREAD;
+                for key in start..(start + NUM_INSERTIONS_PER_THREAD) {
+                    assert_eq!(map.insert(key, -key), None);
+                }
+            });
+        }
+    });
+
+    assert_eq!(
+        map.size(),
+        (NUM_THREADS * NUM_INSERTIONS_PER_THREAD) as usize
+    );
+    for key in 0..(NUM_THREADS * NUM_INSERTIONS_PER_THREAD) {
+        assert_eq!(map.get(&key), Some(-key));
+    }
+}
@@ -0,0 +1,273 @@
+use std::{
+    borrow::Borrow,
+    fmt::{Debug, Display},
+    iter::FusedIterator,
+    ops::RangeBounds,
+    sync::{Arc, RwLock},
+};
+
+use crate::skiplist::{
+    checked_bounds, ConfigError, Cursor, OrdComparator, SkipListConfig, SkipListInner,
+};
+
+/// The versions of one key, oldest first. A `None` value marks a deletion.
+type Chain<V> = Vec<(u64, Option<V>)>;
+
+/// A concurrent ordered map that keeps every version of its values, for
+/// multi-version concurrency control.
+///
+/// Each key holds a chain of `(timestamp, value)` versions, where a deletion
+/// is a version too, a tombstone. A read at `read_ts` sees the newest
+/// version written at or before `read_ts`, so readers pick a timestamp and
+/// get a consistent view of the map as of that time, whatever is written
+/// later. Writing a key twice at the same timestamp replaces the version.
+///
+/// Versions pile up until [`gc`](Self::gc) drops those no read at or after
+/// a watermark can see. Nodes are laid out and locked as in
+/// [`SkipMap`](crate::SkipMap).
+///
+/// ```
+/// use p0::VersionedMap;
+///
+/// let map = VersionedMap::<&str, u32>::new();
+/// map.put("apple", 1, 10);
+/// map.put("apple", 2, 20);
+/// map.put("pear", 3, 15);
+/// map.delete("pear", 30);
+///
+/// assert_eq!(map.get("apple", 5), None);
+/// assert_eq!(map.get("apple", 15), Some(1));
+/// assert_eq!(map.get("apple", 25), Some(2));
+/// assert_eq!(map.range("a".., 20).collect::<Vec<_>>(), vec![("apple", 2), ("pear", 3)]);
+/// assert_eq!(map.range("a".., 30).collect::<Vec<_>>(), vec![("apple", 2)]);
+///
+/// // Reads at 25 or later still see the same values
+/// assert_eq!(map.gc(25), 1);
+/// assert_eq!(map.get("apple", 25), Some(2));
+/// assert_eq!(map.get("pear", 25), Some(3));
+/// ```
+pub struct VersionedMap<K: Ord, V, const MAX_HEIGHT: usize = 14, const SEED: u32 = 15445> {
+    inner: Arc<RwLock<SkipListInner<K, Chain<V>, MAX_HEIGHT, SEED>>>,
+}
+
+impl<K: Ord, V, const MAX_HEIGHT: usize, const SEED: u32> VersionedMap<K, V, MAX_HEIGHT, SEED> {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        VersionedMap {
+            inner: Arc::new(RwLock::new(SkipListInner::new())),
+        }
+    }
+
+    /// Creates an empty map whose tower heights follow `config`; see
+    /// [`SkipList::with_config`](crate::SkipList::with_config).
+    pub fn with_config(config: SkipListConfig) -> Result<Self, ConfigError> {
+        Ok(VersionedMap {
+            inner: Arc::new(RwLock::new(SkipListInner::with_config(config)?)),
+        })
+    }
+
+    /// Returns the number of keys holding at least one version, including
+    /// keys whose newest version is a deletion.
+    pub fn size(&self) -> usize {
+        self.inner.read().unwrap().size()
+    }
+
+    /// Writes `value` under `key` at timestamp `ts`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is new and the map is at its configured
+    /// [capacity](SkipListConfig::capacity).
+    pub fn put(&self, key: K, value: V, ts: u64) {
+        let mut inner = self.inner.write().unwrap();
+        match inner.find(&key) {
+            Some(node) => {
+                let mut node_write_lock = node.write().unwrap();
+                if let Some(chain) = node_write_lock.value_mut() {
+                    write_version(chain, ts, Some(value));
+                }
+            }
+            None => {
+                inner.insert(key, vec![(ts, Some(value))]);
+            }
+        }
+    }
+
+    /// Deletes `key` at timestamp `ts`, by writing a tombstone. Reads before
+    /// `ts` still see the earlier versions. A key with no versions yet gets
+    /// a chain holding just the tombstone, so older writes that arrive later
+    /// stay hidden from reads at or after `ts`. That is why `key` is taken
+    /// by value, as in [`put`](Self::put).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is new and the map is at its configured
+    /// [capacity](SkipListConfig::capacity).
+    pub fn delete(&self, key: K, ts: u64) {
+        let mut inner = self.inner.write().unwrap();
+        match inner.find(&key) {
+            Some(node) => {
+                let mut node_write_lock = node.write().unwrap();
+                if let Some(chain) = node_write_lock.value_mut() {
+                    write_version(chain, ts, None);
+                }
+            }
+            None => {
+                inner.insert(key, vec![(ts, None)]);
+            }
+        }
+    }
+
+    /// Returns a clone of the value `key` held at `read_ts`, or `None` if it
+    /// had none or was deleted by then.
+    pub fn get<Q>(&self, key: &Q, read_ts: u64) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        V: Clone,
+    {
+        let inner = self.inner.read().unwrap();
+        let node = inner.find(key)?;
+        let node_read_lock = node.read().unwrap();
+        node_read_lock
+            .value()
+            .and_then(|chain| visible(chain, read_ts))
+            .cloned()
+    }
+
+    /// Returns an iterator over the entries inside `range` as of `read_ts`,
+    /// in ascending key order. Keys that had no value at `read_ts` are
+    /// skipped. Like [`SkipList::range`](crate::SkipList::range), the
+    /// iterator keeps the map read-locked until it is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if both
+    /// ends are equal and excluded.
+    pub fn range<Q, R>(&self, range: R, read_ts: u64) -> VersionedRange<'_, K, V, MAX_HEIGHT, SEED>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let (start, end) = checked_bounds(&range, &OrdComparator, "VersionedMap");
+        VersionedRange {
+            cursor: Cursor::between(self.inner.read().unwrap(), start, end),
+            read_ts,
+        }
+    }
+
+    /// Drops the versions that no read at `watermark` or later can see, and
+    /// returns how many were dropped. Each key keeps its newest version at
+    /// or before `watermark` and everything after, unless that version is a
+    /// deletion; keys left without versions are removed.
+    ///
+    /// Reads before `watermark` may see wrong values afterwards, so callers
+    /// should only raise it past timestamps no transaction reads at anymore.
+    pub fn gc(&self, watermark: u64) -> usize {
+        let mut inner = self.inner.write().unwrap();
+        let mut dropped = 0;
+        inner.retain(|_, chain| {
+            let newer = chain.partition_point(|(ts, _)| *ts <= watermark);
+            let mut obsolete = newer.saturating_sub(1);
+            // A deletion hides nothing once the versions before it are gone
+            if newer > 0 && chain[obsolete].1.is_none() {
+                obsolete += 1;
+            }
+            chain.drain(..obsolete);
+            dropped += obsolete;
+            !chain.is_empty()
+        });
+        dropped
+    }
+}
+
+/// Writes a version at `ts`, replacing one already written at `ts`.
+fn write_version<V>(chain: &mut Chain<V>, ts: u64, value: Option<V>) {
+    match chain.binary_search_by_key(&ts, |(version_ts, _)| *version_ts) {
+        Ok(index) => chain[index].1 = value,
+        Err(index) => chain.insert(index, (ts, value)),
+    }
+}
+
+/// Returns the value of the newest version at or before `read_ts`.
+fn visible<V>(chain: &Chain<V>, read_ts: u64) -> Option<&V> {
+    let newer = chain.partition_point(|(ts, _)| *ts <= read_ts);
+    chain[..newer].last()?.1.as_ref()
+}
+
+/// An iterator over the entries of a [`VersionedMap`] as of one timestamp,
+/// in ascending key order.
+///
+/// Like [`Iter`](crate::Iter), it holds a read lock on the map until it is
+/// dropped, and clones keys and values as they are yielded.
+pub struct VersionedRange<'a, K, V, const MAX_HEIGHT: usize = 14, const SEED: u32 = 15445> {
+    cursor: Cursor<'a, K, Chain<V>, MAX_HEIGHT, SEED, OrdComparator>,
+    read_ts: u64,
+}
+
+impl<K: Ord + Clone, V: Clone, const MAX_HEIGHT: usize, const SEED: u32> Iterator
+    for VersionedRange<'_, K, V, MAX_HEIGHT, SEED>
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        let read_ts = self.read_ts;
+        loop {
+            let entry = self.cursor.next_with(|key, chain| {
+                visible(chain, read_ts).map(|value| (key.clone(), value.clone()))
+            })?;
+            if entry.is_some() {
+                return entry;
+            }
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone, const MAX_HEIGHT: usize, const SEED: u32> DoubleEndedIterator
+    for VersionedRange<'_, K, V, MAX_HEIGHT, SEED>
+{
+    fn next_back(&mut self) -> Option<(K, V)> {
+        let read_ts = self.read_ts;
+        loop {
+            let entry = self.cursor.next_back_with(|key, chain| {
+                visible(chain, read_ts).map(|value| (key.clone(), value.clone()))
+            })?;
+            if entry.is_some() {
+                return entry;
+            }
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone, const MAX_HEIGHT: usize, const SEED: u32> FusedIterator
+    for VersionedRange<'_, K, V, MAX_HEIGHT, SEED>
+{
+}
+
+impl<K: Ord, V, const MAX_HEIGHT: usize, const SEED: u32> Default
+    for VersionedMap<K, V, MAX_HEIGHT, SEED>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Debug, V, const MAX_HEIGHT: usize, const SEED: u32> Display
+    for VersionedMap<K, V, MAX_HEIGHT, SEED>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.inner.read().unwrap(), f)
+    }
+}
+
+impl<K: Ord + Debug, V, const MAX_HEIGHT: usize, const SEED: u32> Debug
+    for VersionedMap<K, V, MAX_HEIGHT, SEED>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.inner.read().unwrap(), f)
+    }
+}
+
+#[cfg(test)]
+mod versioned_test;
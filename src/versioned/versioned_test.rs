@@ -0,0 +1,179 @@
+use std::sync::Arc;
+use std::sync::Barrier;
+use std::thread::scope;
+
+use super::*;
+
+#[test]
+fn put_get_test() {
+    let map = VersionedMap::<i32, String>::new();
+    map.put(1, "a".to_string(), 10);
+    map.put(1, "b".to_string(), 20);
+    map.put(1, "c".to_string(), 30);
+
+    assert_eq!(map.get(&1, 9), None);
+    assert_eq!(map.get(&1, 10), Some("a".to_string()));
+    assert_eq!(map.get(&1, 19), Some("a".to_string()));
+    assert_eq!(map.get(&1, 20), Some("b".to_string()));
+    assert_eq!(map.get(&1, u64::MAX), Some("c".to_string()));
+    assert_eq!(map.get(&2, 20), None);
+    assert_eq!(map.size(), 1);
+}
+
+#[test]
+fn out_of_order_put_test() {
+    let map = VersionedMap::<i32, i32>::new();
+    map.put(1, 30, 30);
+    map.put(1, 10, 10);
+    map.put(1, 20, 20);
+    // The same timestamp replaces the version
+    map.put(1, 21, 20);
+
+    assert_eq!(map.get(&1, 15), Some(10));
+    assert_eq!(map.get(&1, 25), Some(21));
+    assert_eq!(map.get(&1, 35), Some(30));
+}
+
+#[test]
+fn delete_test() {
+    let map = VersionedMap::<i32, i32>::new();
+    map.put(1, 100, 10);
+    map.delete(1, 20);
+    map.put(1, 300, 30);
+    // Deleting a key without versions keeps its tombstone
+    map.delete(2, 20);
+
+    assert_eq!(map.get(&1, 15), Some(100));
+    assert_eq!(map.get(&1, 20), None);
+    assert_eq!(map.get(&1, 25), None);
+    assert_eq!(map.get(&1, 30), Some(300));
+    assert_eq!(map.get(&2, 25), None);
+    assert_eq!(map.size(), 2);
+}
+
+#[test]
+fn delete_then_older_put_test() {
+    let map = VersionedMap::<i32, i32>::new();
+    map.delete(1, 20);
+    map.put(1, 10, 10);
+
+    assert_eq!(map.get(&1, 15), Some(10));
+    assert_eq!(map.get(&1, 20), None);
+    assert_eq!(map.get(&1, 25), None);
+
+    // Once no reader needs the older version, gc drops the whole chain
+    map.gc(30);
+    assert_eq!(map.size(), 0);
+}
+
+#[test]
+fn range_test() {
+    let map = VersionedMap::<i32, i32>::new();
+    for key in 0..10 {
+        map.put(key, key, 10);
+    }
+    for key in (0..10).step_by(2) {
+        map.delete(key, 20);
+    }
+    map.put(4, 40, 30);
+
+    assert_eq!(
+        map.range(2..6, 10).collect::<Vec<_>>(),
+        vec![(2, 2), (3, 3), (4, 4), (5, 5)]
+    );
+    assert_eq!(
+        map.range(2..6, 20).collect::<Vec<_>>(),
+        vec![(3, 3), (5, 5)]
+    );
+    assert_eq!(
+        map.range(2..6, 30).collect::<Vec<_>>(),
+        vec![(3, 3), (4, 40), (5, 5)]
+    );
+    assert_eq!(
+        map.range(.., 30)
+            .rev()
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>(),
+        vec![9, 7, 5, 4, 3, 1]
+    );
+    assert_eq!(map.range(.., 5).count(), 0);
+}
+
+#[test]
+fn gc_test() {
+    let map = VersionedMap::<i32, i32>::new();
+    for ts in 1..=5 {
+        map.put(1, ts as i32, ts * 10);
+    }
+    map.put(2, 1, 10);
+    map.delete(2, 20);
+    map.put(3, 1, 10);
+    map.delete(3, 20);
+    map.put(3, 2, 40);
+
+    // Key 1 keeps its version at 20 and later ones, key 2 goes away, key 3
+    // drops the put and the tombstone before the put at 40
+    assert_eq!(map.gc(25), 1 + 2 + 2);
+    assert_eq!(map.size(), 2);
+    for read_ts in [25, 30, 45, 50] {
+        assert_eq!(map.get(&1, read_ts), Some(read_ts as i32 / 10));
+        assert_eq!(map.get(&2, read_ts), None);
+    }
+    assert_eq!(map.get(&3, 30), None);
+    assert_eq!(map.get(&3, 40), Some(2));
+
+    assert_eq!(map.gc(25), 0);
+    assert_eq!(map.gc(u64::MAX), 3);
+    assert_eq!(
+        map.range(.., u64::MAX).collect::<Vec<_>>(),
+        vec![(1, 5), (3, 2)]
+    );
+}
+
+#[test]
+fn borrowed_key_test() {
+    let map = VersionedMap::<String, i32>::new();
+    map.put("a".to_string(), 1, 10);
+    map.put("b".to_string(), 2, 10);
+    map.delete("a".to_string(), 20);
+
+    assert_eq!(map.get("a", 10), Some(1));
+    assert_eq!(map.get("a", 20), None);
+    assert_eq!(
+        map.range::<str, _>(.., 20).collect::<Vec<_>>(),
+        vec![("b".to_string(), 2)]
+    );
+}
+
+#[test]
+fn concurrent_put_test() {
+    const NUM_THREADS: u64 = 10;
+    const NUM_VERSIONS_PER_THREAD: u64 = 100;
+    const NUM_KEYS: i32 = 10;
+
+    let map = Arc::new(VersionedMap::<i32, u64>::new());
+    let barrier = Arc::new(Barrier::new(NUM_THREADS as usize));
+
+    scope(|s| {
+        for i in 0..NUM_THREADS {
+            let map = Arc::clone(&map);
+            let barrier = Arc::clone(&barrier);
+            s.spawn(move || {
+                barrier.wait();
+                for n in 0..NUM_VERSIONS_PER_THREAD {
+                    let ts = n * NUM_THREADS + i;
+                    for key in 0..NUM_KEYS {
+                        map.put(key, ts, ts);
+                    }
+                }
+            });
+        }
+    });
+
+    assert_eq!(map.size(), NUM_KEYS as usize);
+    for ts in 0..(NUM_THREADS * NUM_VERSIONS_PER_THREAD) {
+        assert!(map.range(.., ts).all(|(_, value)| value == ts));
+    }
+    let versions = NUM_THREADS * NUM_VERSIONS_PER_THREAD * NUM_KEYS as u64;
+    assert_eq!(map.gc(u64::MAX), versions as usize - NUM_KEYS as usize);
+}